@@ -23,7 +23,7 @@ function __timetrack_show_modes
 end
 function __timetrack_range_suggestions
     set -l token (commandline -ct)
-    echo -e 0\n1\nhour\nday\nweek\nmonth
+    echo -e 0\n1\nhour\nday\nweek\nmonth\nyesterday\nlast-week\nlast-month
     if string match -qr '^(-[a-z]+)?[0-9]+' -- $token
         set -l n (string match -r '[0-9]+' -- $token)
         test "$n" = 1 \
@@ -85,8 +85,63 @@ complete -c timetrack \
     -n '__fish_seen_subcommand_from start'
 complete -c timetrack -fl verbose \
     -n '__fish_seen_subcommand_from start'
+complete -c timetrack -rfl at \
+    -n '__fish_seen_subcommand_from start end pause resume'
+complete -c timetrack -rfl ago \
+    -n '__fish_seen_subcommand_from start end pause resume'
 complete -c timetrack -fs v \
     -n '__fish_seen_subcommand_from start'
+complete -c timetrack -rfl tag \
+    -n '__fish_seen_subcommand_from start amend show'
+complete -c timetrack -rfs t \
+    -n '__fish_seen_subcommand_from start amend'
+
+# Subcommand amend
+complete -c timetrack -f \
+    -n '__fish_seen_subcommand_from amend' \
+    -a "(__timetrack_activities)"
+complete -c timetrack \
+    -rfl attendance \
+    -n '__fish_seen_subcommand_from amend' \
+    -a "(__timetrack_attendance_types)"
+complete -c timetrack \
+    -rfs a \
+    -n '__fish_seen_subcommand_from amend' \
+    -a "(__timetrack_attendance_types)"
+complete -c timetrack \
+    -rfl description \
+    -n '__fish_seen_subcommand_from amend'
+complete -c timetrack -rfl at \
+    -n '__fish_seen_subcommand_from amend'
+complete -c timetrack -rfl ago \
+    -n '__fish_seen_subcommand_from amend'
+
+# Subcommand migrate-storage
+complete -c timetrack -f \
+    -n '__fish_seen_subcommand_from migrate-storage' \
+    -a "tsv sqlite"
+complete -c timetrack -fl force \
+    -n '__fish_seen_subcommand_from migrate-storage'
+
+# Subcommand calendar
+complete -c timetrack -f \
+    -n '__fish_seen_subcommand_from calendar' \
+    -n 'not __fish_seen_subcommand_from mark unmark ls help' \
+    -a "mark\t'Mark days off' unmark\t'Remove marks from days off' ls\t'List days'"
+complete -c timetrack -f \
+    -n '__fish_seen_subcommand_from calendar' \
+    -n '__fish_seen_subcommand_from mark' \
+    -n 'not __fish_seen_subcommand_from vacation sick' \
+    -a "vacation sick"
+
+# Subcommand balance
+complete -c timetrack -fs i -l include-today \
+    -n '__fish_seen_subcommand_from balance'
+
+# Subcommand check-compliance
+complete -c timetrack -f \
+    -n '__fish_seen_subcommand_from check-compliance' \
+    -a "yesterday last-week last-month"
 
 # Subcommand show
 complete -c timetrack -f \
@@ -100,9 +155,27 @@ complete -c timetrack \
     -rfl last \
     -n '__fish_seen_subcommand_from show' \
     -a "(__timetrack_range_suggestions)"
+complete -c timetrack \
+    -rfl activity \
+    -n '__fish_seen_subcommand_from show' \
+    -a "(__timetrack_activities)"
+complete -c timetrack \
+    -rfl attendance \
+    -n '__fish_seen_subcommand_from show' \
+    -a "(__timetrack_attendance_types)"
+complete -c timetrack \
+    -rfl wbs \
+    -n '__fish_seen_subcommand_from show'
+complete -c timetrack \
+    -rfl grep \
+    -n '__fish_seen_subcommand_from show'
 complete -c timetrack \
     -fl machine-readable \
     -n '__fish_seen_subcommand_from show'
+complete -c timetrack \
+    -rfl format \
+    -n '__fish_seen_subcommand_from show' \
+    -a "json jsonl csv tsv"
 complete -c timetrack \
     -fs m \
     -n '__fish_seen_subcommand_from show'
@@ -136,6 +209,11 @@ complete -c timetrack \
     -fl machine-readable \
     -n '__fish_seen_subcommand_from activity' \
     -n '__fish_seen_subcommand_from ls'
+complete -c timetrack \
+    -rfl format \
+    -n '__fish_seen_subcommand_from activity' \
+    -n '__fish_seen_subcommand_from ls' \
+    -a "json jsonl csv tsv"
 complete -c timetrack \
     -fs m \
     -n '__fish_seen_subcommand_from activity' \
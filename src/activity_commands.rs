@@ -1,38 +1,309 @@
-use std::{fs, rc::Rc, str::FromStr};
+use std::{
+    collections::{HashMap, HashSet},
+    rc::Rc,
+    str::FromStr,
+};
 
-use color_eyre::eyre::{Result, format_err};
+use color_eyre::{
+    Section,
+    eyre::{Context, Result, format_err},
+};
+use owo_colors::{OwoColorize, Stream};
 
 use crate::{
-    NONE_PRINT_VALUE, cli, files, print_smart_list, print_smart_table,
-    trackable::{Activity, ActivityCategory, ActivityItemRef, ActivityLeaf, PrintableActivityItem},
+    NONE_PRINT_VALUE,
+    activity_entry::ActivityEntry,
+    cli,
+    entry_commands::{get_all_entries, rewrite_history, sanitize_description},
+    files,
+    format_string::FormatString,
+    get_existing_config,
+    machine_readable::print_records,
+    picker, print_smart_list, print_smart_table, storage,
+    trackable::{
+        Activity, ActivityCategory, ActivityItemRef, ActivityLeaf, BUILTIN_ACTIVITY_IDLE_NAME,
+        InsertError, PrintableActivityItem,
+    },
 };
 
+/// Activities need to be at least this similar to an unknown name to be suggested
+const MIN_SUGGESTION_SIMILARITY: f64 = 0.8;
+const MAX_SUGGESTIONS: usize = 3;
+
 pub fn set_activity(set_opts: &cli::SetActivity) -> Result<()> {
-    let activities = get_all_trackable_activities()?;
-    let hierarchy = ActivityCategory::from(activities);
+    let mut path = split_activity_path(&set_opts.name);
+    let name = path
+        .pop()
+        .ok_or(format_err!("activity name must not be empty"))?;
+    check_not_builtin(path.first().unwrap_or(&name), "changed")?;
+    check_activity_path(path.iter().chain(Some(&name)))?;
+    if set_opts.wbs.contains(['\t', '\n', '\r']) {
+        return Err(format_err!("wbs must not contain tabs or newlines"));
+    }
+
+    if let Some(alias) = set_opts.aliases.iter().find(|a| {
+        a.is_empty() || a.contains(|c: char| c.is_whitespace() || ['/', ',', '='].contains(&c))
+    }) {
+        return Err(format_err!(
+            "invalid alias '{alias}', aliases must not be empty or contain whitespace, '/', ',' or '='"
+        ));
+    }
 
-    todo!()
+    let config = get_existing_config()?;
+    if let Some(config) = &config {
+        config
+            .assert_format
+            .check_wbs(&set_opts.wbs)
+            .map_err(|e| format_err!(e))?;
+        if let Some(attendance) = &set_opts.attendance
+            && !config.attendance_types.contains_key(attendance)
+        {
+            return Err(format_err!("attendance type '{attendance}' is not defined"))
+                .with_note(|| "edit your config file to add a new attendance type");
+        }
+    }
+
+    let description = set_opts.description.as_deref().map(sanitize_description);
+    if let Some(description) = &description {
+        FormatString::from_str(description)
+            .wrap_err("invalid default description")
+            .with_note(|| "use $$ for a literal $")?;
+    }
+    let _lock = files::lock_activity_file()?;
+    let activities = get_defined_activities()?;
+    let full_path = path.iter().chain(Some(&name)).copied().collect::<Vec<_>>();
+
+    // An overwritten activity keeps its aliases and attendance unless new ones are given
+    let existing = activities
+        .iter()
+        .find(|a| a.full_path() == full_path.join("/"));
+    let aliases = match (set_opts.aliases.is_empty(), existing) {
+        (true, Some(existing)) => existing.aliases().iter().map(|a| a.to_string()).collect(),
+        _ => set_opts.aliases.clone(),
+    };
+    let attendance = set_opts
+        .attendance
+        .clone()
+        .or_else(|| existing?.attendance().map(str::to_string));
+    check_alias_conflicts(
+        &activities,
+        config.as_ref().map(|c| &c.aliases),
+        &full_path.join("/"),
+        &aliases,
+    )?;
+    let leaf = ActivityLeaf::new(name, &set_opts.wbs, description.as_deref())
+        .with_attendance(attendance.as_deref())
+        .with_aliases(&aliases);
+    let mut hierarchy = ActivityCategory::from(activities);
+    let replaced = match hierarchy.insert_leaf(&path, leaf, set_opts.force) {
+        Err(e @ InsertError::AlreadyExists(_)) => {
+            Err(e).with_note(|| "use --force to overwrite existing activities")
+        }
+        res => Ok(res?),
+    }?;
+    write_activities(&hierarchy.to_activities_sorted())?;
+
+    let action = if replaced.is_some() {
+        "Updated"
+    } else {
+        "Created"
+    };
+    println!(
+        "{action} activity '{}'",
+        full_path
+            .join("/")
+            .if_supports_color(Stream::Stdout, |n| n.green())
+    );
+    Ok(())
+}
+
+/// Fail if any of `aliases` for the activity at `path` is already used elsewhere
+fn check_alias_conflicts(
+    activities: &[Activity],
+    config_aliases: Option<&HashMap<String, String>>,
+    path: &str,
+    aliases: &[String],
+) -> Result<()> {
+    for alias in aliases {
+        let taken_by = activities
+            .iter()
+            .filter(|a| a.full_path() != path)
+            .find(|a| a.full_path() == *alias || a.aliases().iter().any(|b| **b == **alias))
+            .map(|a| a.full_path())
+            .or_else(|| config_aliases?.get(alias).filter(|p| *p != path).cloned());
+        if let Some(other) = taken_by {
+            return Err(format_err!("alias '{alias}' is already used by {other}"));
+        }
+    }
+    Ok(())
 }
 
 pub fn move_activity(move_opts: &cli::MoveActivity) -> Result<()> {
-    todo!()
+    let from = split_activity_path(&move_opts.from);
+    let to = split_activity_path(&move_opts.to);
+    let (Some((from_name, from_parent)), Some((to_name, to_parent))) =
+        (from.split_last(), to.split_last())
+    else {
+        return Err(format_err!("activity names must not be empty"));
+    };
+    for path in [&from, &to] {
+        check_not_builtin(path[0], "moved")?;
+    }
+    check_activity_path(&to)?;
+    if to.starts_with(&from) {
+        return Err(format_err!(
+            "can't move {} into itself",
+            move_opts.from.trim_matches('/')
+        ));
+    }
+
+    let _lock = files::lock_activity_file()?;
+    let activities = get_defined_activities()?;
+    let mut hierarchy = ActivityCategory::from(activities.clone());
+    let item = hierarchy.remove_item(from_parent, from_name)?;
+    hierarchy.insert_item(to_parent, to_name, item)?;
+
+    let (from, to) = (from.join("/"), to.join("/"));
+    write_activities(&hierarchy.to_activities_sorted())?;
+    if move_opts.rewrite_history {
+        // Entries must never reference activities that don't exist, so undo the move on failure
+        let rewritten = match rename_in_entries(&from, &to) {
+            Ok(rewritten) => rewritten,
+            Err(e) => {
+                write_activities(&activities)
+                    .wrap_err("failed to restore activities after failing to rewrite entries")?;
+                return Err(e);
+            }
+        };
+        println!("Rewrote {rewritten} recorded entries");
+    }
+
+    println!(
+        "Moved '{}' to '{}'",
+        from.if_supports_color(Stream::Stdout, |n| n.red()),
+        to.if_supports_color(Stream::Stdout, |n| n.green())
+    );
+    Ok(())
 }
 
-pub fn remove_activity(set_opts: &cli::RemoveActivity) -> Result<()> {
-    todo!()
+/// Rename activity `from` and everything below it to `to` in the entry file
+/// Returns the number of changed entries
+fn rename_in_entries(from: &str, to: &str) -> Result<usize> {
+    let _lock = files::lock_entry_file()?;
+    let mut rewritten = 0;
+    let entries = get_all_entries()?
+        .into_iter()
+        .map(|entry| match entry {
+            ActivityEntry::Start(start) => {
+                let renamed = match start.name().strip_prefix(from) {
+                    Some("") => Some(to.to_string()),
+                    Some(rest) if rest.starts_with('/') => Some(format!("{to}{rest}")),
+                    _ => None,
+                };
+                match renamed {
+                    Some(name) => {
+                        rewritten += 1;
+                        ActivityEntry::Start(start.with_name(&name))
+                    }
+                    None => ActivityEntry::Start(start),
+                }
+            }
+            end => end,
+        })
+        .collect::<Vec<_>>();
+    if rewritten > 0 {
+        rewrite_history(&entries)?;
+    }
+    Ok(rewritten)
+}
+
+pub fn remove_activity(remove_opts: &cli::RemoveActivity) -> Result<()> {
+    let mut path = split_activity_path(&remove_opts.name);
+    let name = path
+        .pop()
+        .ok_or(format_err!("activity name must not be empty"))?;
+    if path.is_empty() && name == BUILTIN_ACTIVITY_IDLE_NAME {
+        return Err(format_err!(
+            "{name} is a builtin activity and can't be removed"
+        ));
+    }
+
+    let _lock = files::lock_activity_file()?;
+    let mut hierarchy = ActivityCategory::from(get_defined_activities()?);
+    let full_path = path.iter().chain(Some(&name)).copied().collect::<Vec<_>>();
+    let removed_paths = match hierarchy.get_item_at(&full_path)? {
+        ActivityItemRef::Leaf(_) => vec![full_path.join("/")],
+        ActivityItemRef::Category(_) if !remove_opts.recursive => {
+            return Err(format_err!(
+                "{} is an activity category",
+                full_path.join("/")
+            ))
+            .with_note(|| "use --recursive to remove activity categories");
+        }
+        ActivityItemRef::Category(category) => category
+            .to_activities_sorted()
+            .iter()
+            .map(|a| format!("{}/{}", full_path.join("/"), a.full_path()))
+            .collect(),
+    };
+
+    print_entry_references(&removed_paths)?;
+    hierarchy.remove_item(&path, name)?;
+    write_activities(&hierarchy.to_activities_sorted())?;
+
+    for removed in removed_paths {
+        println!(
+            "Removed activity '{}'",
+            removed.if_supports_color(Stream::Stdout, |n| n.red())
+        );
+    }
+    Ok(())
+}
+
+/// Report how many recorded entries still reference any of `activity_paths`
+fn print_entry_references(activity_paths: &[String]) -> Result<()> {
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    for entry in get_all_entries()? {
+        if let ActivityEntry::Start(start) = entry
+            && let Some(path) = activity_paths.iter().find(|p| *p == start.name())
+        {
+            *counts.entry(path).or_default() += 1;
+        }
+    }
+    if counts.is_empty() {
+        return Ok(());
+    }
+
+    let total: usize = counts.values().sum();
+    println!("{total} recorded entries still reference removed activities:");
+    let list = activity_paths
+        .iter()
+        .filter_map(|p| counts.get(p.as_str()).map(|c| (p, c)))
+        .collect::<Vec<_>>();
+    print_smart_list!(list);
+    Ok(())
 }
 
 pub fn list_activities(opts: &cli::ListActivities) -> Result<()> {
     let search_path = opts
         .name
         .as_deref()
-        .map(|s| s.split("/").filter(|s| !s.is_empty()).collect::<Vec<_>>())
+        .map(split_activity_path)
         .unwrap_or_default();
     let activities = get_all_trackable_activities()?;
     let hierarchy = ActivityCategory::from(activities);
-    match hierarchy.get_item_at(&search_path)? {
-        ActivityItemRef::Leaf(l) => print_single(l, opts.machine_readable),
-        ActivityItemRef::Category(c) => print_hierarchy(c, opts.recursive, opts.machine_readable),
+    match (hierarchy.get_item_at(&search_path)?, &opts.format) {
+        (ActivityItemRef::Leaf(l), Some(format)) => {
+            print_records([PrintableActivityItem::ActivityLeaf(l)], format)?
+        }
+        (ActivityItemRef::Category(c), Some(format)) => {
+            print_records(get_printable_items(c, opts.recursive).items(), format)?
+        }
+        (ActivityItemRef::Leaf(l), None) => print_single(l, opts.machine_readable),
+        (ActivityItemRef::Category(c), None) => print_activities(
+            get_printable_items(c, opts.recursive).items(),
+            opts.machine_readable,
+        ),
     };
 
     Ok(())
@@ -45,27 +316,44 @@ fn print_single(leaf: &ActivityLeaf, machine_readable: bool) {
         print_smart_list! {
             "Name" => leaf.name(),
             "WBS" => leaf.wbs(),
+            "Attendance" => leaf.attendance().unwrap_or_default(),
             "Description" => leaf.description().unwrap_or_default(),
+            "Aliases" => &leaf.aliases().join(", "),
         }
     }
 }
 
-fn print_hierarchy(hierarchy: &ActivityCategory, recursive: bool, machine_readable: bool) {
+/// Contents of an activity category, either listed directly or recursively expanded
+enum CategoryContents<'a> {
+    Expanded(Vec<Activity>),
+    Direct(Vec<&'a Rc<str>>, Vec<&'a ActivityLeaf>),
+}
+impl CategoryContents<'_> {
+    fn items(&self) -> Vec<PrintableActivityItem<'_>> {
+        match self {
+            CategoryContents::Expanded(expanded) => expanded
+                .iter()
+                .map(PrintableActivityItem::Activity)
+                .collect(),
+            CategoryContents::Direct(branches, leafs) => branches
+                .iter()
+                .map(|s| PrintableActivityItem::CategoryName(s))
+                .chain(leafs.iter().map(|s| PrintableActivityItem::ActivityLeaf(s)))
+                .collect(),
+        }
+    }
+}
+
+fn get_printable_items(hierarchy: &ActivityCategory, recursive: bool) -> CategoryContents<'_> {
     if recursive {
-        let expanded = hierarchy.to_activities_sorted();
-        let printable = expanded.iter().map(PrintableActivityItem::Activity);
-        print_activities(printable, machine_readable);
+        CategoryContents::Expanded(hierarchy.to_activities_sorted())
     } else {
         let mut branches = Vec::from_iter(hierarchy.branches.keys());
         let mut leafs = Vec::from_iter(hierarchy.leafs.values());
         leafs.sort_unstable_by(|a, b| a.name().cmp(b.name()));
         branches.sort_unstable();
-        let printable = branches
-            .iter()
-            .map(|s| PrintableActivityItem::CategoryName(s))
-            .chain(leafs.iter().map(|s| PrintableActivityItem::ActivityLeaf(s)));
-        print_activities(printable, machine_readable);
-    };
+        CategoryContents::Direct(branches, leafs)
+    }
 }
 fn print_activities<'a, I>(activities: I, print_machine_readable: bool)
 where
@@ -85,7 +373,9 @@ where
 {
     let mut col_name: Vec<Rc<str>> = Vec::new();
     let mut col_wbs: Vec<Rc<str>> = Vec::new();
+    let mut col_attendance: Vec<Rc<str>> = Vec::new();
     let mut col_descr: Vec<Rc<str>> = Vec::new();
+    let mut col_aliases: Vec<Rc<str>> = Vec::new();
     let none_value: Rc<str> = NONE_PRINT_VALUE.into();
 
     for activity in activities {
@@ -97,35 +387,228 @@ where
             Some(w) => Rc::from(w),
             None => none_value.clone(),
         };
+        let attendance = match activity.attendance() {
+            Some(a) => Rc::from(a),
+            None => none_value.clone(),
+        };
+        let aliases = match activity.aliases() {
+            [] => none_value.clone(),
+            aliases => Rc::from(aliases.join(", ")),
+        };
         col_name.push(activity.display_name());
         col_descr.push(description);
         col_wbs.push(wbs);
+        col_attendance.push(attendance);
+        col_aliases.push(aliases);
     }
 
     print_smart_table! {
         "Name" => col_name,
         "WBS" => col_wbs,
+        "Attendance" => col_attendance,
         "Default Description" => col_descr,
+        "Aliases" => col_aliases,
     };
 }
 
-pub fn get_trackable_activity(activity_path: &str) -> Result<Activity> {
-    get_all_trackable_activities()?
+/// Find the activity called `name`, which is a full path, an alias or an unambiguous path suffix
+pub fn get_trackable_activity(name: &str) -> Result<Activity> {
+    let activities = get_all_trackable_activities()?;
+    let config_aliases = get_existing_config()?
+        .map(|c| c.aliases)
+        .unwrap_or_default();
+    resolve_activity(activities, &config_aliases, name)
+}
+
+fn resolve_activity(
+    mut activities: Vec<Activity>,
+    config_aliases: &HashMap<String, String>,
+    name: &str,
+) -> Result<Activity> {
+    if let Some(i) = activities.iter().position(|a| a.full_path() == name) {
+        return Ok(activities.swap_remove(i));
+    }
+
+    let mut aliased: Vec<usize> = activities
+        .iter()
+        .enumerate()
+        .filter(|(_, a)| a.aliases().iter().any(|alias| &**alias == name))
+        .map(|(i, _)| i)
+        .collect();
+    if let Some(path) = config_aliases.get(name) {
+        let i = activities
+            .iter()
+            .position(|a| a.full_path() == *path)
+            .ok_or_else(|| format_err!("alias '{name}' refers to {path}, which does not exist"))
+            .with_note(|| "aliases are defined in the [aliases] section of your config")?;
+        if !aliased.contains(&i) {
+            aliased.push(i);
+        }
+    }
+    if !aliased.is_empty() {
+        return pick_unambiguous(activities, aliased, name);
+    }
+
+    let suffix = format!("/{}", name.trim_matches('/'));
+    let matching: Vec<usize> = activities
+        .iter()
+        .enumerate()
+        .filter(|(_, a)| a.full_path().ends_with(&suffix))
+        .map(|(i, _)| i)
+        .collect();
+    if !matching.is_empty() {
+        return pick_unambiguous(activities, matching, name);
+    }
+
+    let suggestions = suggest_activities(&activities, config_aliases, name);
+    let error = format_err!("{name} does not exist");
+    if suggestions.is_empty() {
+        return Err(error).with_note(|| "use `timetrack activity ls -r` to list all activities");
+    }
+    Err(error).with_note(|| format!("did you mean {}?", suggestions.join(", ")))
+}
+
+/// Return the only activity at `candidates`, or list all of them if there are several
+fn pick_unambiguous(
+    mut activities: Vec<Activity>,
+    candidates: Vec<usize>,
+    name: &str,
+) -> Result<Activity> {
+    if let [i] = candidates[..] {
+        return Ok(activities.swap_remove(i));
+    }
+    let mut paths: Vec<String> = candidates
+        .iter()
+        .map(|&i| activities[i].full_path())
+        .collect();
+    paths.sort_unstable();
+    Err(format_err!("'{name}' is ambiguous"))
+        .with_note(|| format!("it could mean any of {}", paths.join(", ")))
+}
+
+/// Activity paths whose path, name or alias is similar to `name`, most similar first
+fn suggest_activities(
+    activities: &[Activity],
+    config_aliases: &HashMap<String, String>,
+    name: &str,
+) -> Vec<String> {
+    let mut scored: Vec<(f64, String)> = Vec::new();
+    for activity in activities {
+        let path = activity.full_path();
+        let score = [path.as_str(), activity.leaf_name()]
+            .into_iter()
+            .chain(activity.aliases().iter().map(|a| &**a))
+            .chain(
+                config_aliases
+                    .iter()
+                    .filter(|(_, p)| **p == path)
+                    .map(|(alias, _)| alias.as_str()),
+            )
+            .map(|candidate| strsim::jaro_winkler(name, candidate))
+            .fold(0.0, f64::max);
+        if score >= MIN_SUGGESTION_SIMILARITY {
+            scored.push((score, path));
+        }
+    }
+    scored.sort_by(|a, b| b.0.total_cmp(&a.0).then_with(|| a.1.cmp(&b.1)));
+    scored
         .into_iter()
-        .find(|activity| activity.full_path() == activity_path)
-        .ok_or(format_err!("{activity_path} does not exist"))
+        .take(MAX_SUGGESTIONS)
+        .map(|(_, path)| path)
+        .collect()
+}
+
+/// Let the user pick an activity in the terminal, most recently used activities first
+/// Returns None if the user cancelled
+pub fn pick_trackable_activity() -> Result<Option<Activity>> {
+    let mut activities =
+        ActivityCategory::from(get_all_trackable_activities()?).to_activities_sorted();
+    sort_by_recent_use(&mut activities)?;
+    let items = activities
+        .iter()
+        .map(|a| match a.aliases() {
+            [] => a.full_path(),
+            aliases => format!("{} ({})", a.full_path(), aliases.join(", ")),
+        })
+        .collect::<Vec<_>>();
+    let picked = picker::pick("Activity", &items)?;
+    Ok(picked.map(|i| activities.swap_remove(i)))
+}
+
+/// Stable sort of `activities` by the time they were last started, most recent first
+fn sort_by_recent_use(activities: &mut [Activity]) -> Result<()> {
+    let paths: HashSet<String> = activities.iter().map(Activity::full_path).collect();
+    let mut recency: HashMap<String, usize> = HashMap::new();
+    for entry in storage::open()?.entries_backwards()? {
+        if recency.len() == paths.len() {
+            break;
+        }
+        if let ActivityEntry::Start(start) = entry?
+            && paths.contains(start.name())
+        {
+            let rank = recency.len();
+            recency.entry(start.name().to_string()).or_insert(rank);
+        }
+    }
+    activities.sort_by_key(|a| recency.get(&a.full_path()).copied().unwrap_or(usize::MAX));
+    Ok(())
 }
 
 pub fn get_all_trackable_activities() -> Result<Vec<Activity>> {
-    let path = files::get_activity_file_path()?;
-    let builtin_idle = Activity::builtin_idle();
-    if !fs::exists(&path)? {
-        return Ok(vec![builtin_idle]);
-    }
-    let mut activities = fs::read_to_string(path)?
-        .lines()
-        .map(Activity::from_str)
-        .collect::<std::result::Result<Vec<_>, _>>()?;
-    activities.push(builtin_idle);
+    let mut activities = get_defined_activities()?;
+    activities.push(Activity::builtin_idle());
     Ok(activities)
 }
+
+/// Read defined activities, excluding builtin activities
+fn get_defined_activities() -> Result<Vec<Activity>> {
+    storage::open()?.activities()
+}
+
+/// Atomically overwrite all defined activities with `activities`
+/// The caller must hold the lock on the activity file
+fn write_activities(activities: &[Activity]) -> Result<()> {
+    storage::open()?.rewrite_activities(activities)
+}
+
+/// Fail if any part of an activity path would break the line based activity file
+fn check_activity_path<'a>(path: impl IntoIterator<Item = &'a &'a str>) -> Result<()> {
+    match path.into_iter().find(|p| p.contains(['\t', '\n', '\r'])) {
+        Some(part) => Err(format_err!(
+            "invalid activity name '{}', names must not contain tabs or newlines",
+            part.escape_debug()
+        )),
+        None => Ok(()),
+    }
+}
+
+/// Fail for paths starting with a builtin activity, which can't be changed or contain others
+fn check_not_builtin(first: &str, action: &str) -> Result<()> {
+    match first == BUILTIN_ACTIVITY_IDLE_NAME {
+        true => Err(format_err!(
+            "{first} is a builtin activity and can't be {action} or used as a category"
+        )),
+        false => Ok(()),
+    }
+}
+
+fn split_activity_path(path: &str) -> Vec<&str> {
+    path.split("/").filter(|s| !s.is_empty()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builtin_activity_is_no_category() {
+        for path in ["idle", "/idle/", "idle/x", "idle/x/y"] {
+            let first = split_activity_path(path)[0];
+            let e = check_not_builtin(first, "changed").unwrap_err();
+            assert!(e.to_string().contains("builtin activity"), "{path}: {e}");
+        }
+        for path in ["work/idle", "idler", "Idle/x"] {
+            assert!(check_not_builtin(split_activity_path(path)[0], "moved").is_ok());
+        }
+    }
+}
@@ -1,10 +1,18 @@
 use std::{fmt::Display, rc::Rc, str::FromStr};
 
 use chrono::{DateTime, Local, NaiveTime, TimeDelta};
+use serde::Serialize;
+
+use crate::machine_readable::Record;
 
 pub mod activity_groupings;
 
 const END_SENTINEL: &str = "__END";
+/// Marks an end entry as a pause, older versions ignore it and read a plain end
+const PAUSE_MARKER: &str = "pause";
+/// Tags are stored comma separated in an optional field, which older versions ignore
+/// Tags can't contain the separator, see [parse_tag]
+pub const TAG_SEPARATOR: char = ',';
 
 #[derive(Debug, Clone)]
 pub enum ParseEntryError {
@@ -34,32 +42,54 @@ impl From<chrono::format::ParseError> for ParseEntryError {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct TrackedActivity {
+    #[serde(flatten)]
     pub start_entry: ActivityStart,
     pub end: Option<DateTime<Local>>,
+    /// Whether the activity was ended by `timetrack pause`
+    pub paused: bool,
+}
+impl Record for TrackedActivity {
+    const FIELDS: &'static [&'static str] = &[
+        "start",
+        "activity",
+        "attendance",
+        "wbs",
+        "description",
+        "tags",
+        "end",
+        "paused",
+    ];
 }
 impl TrackedActivity {
     pub fn new(start_entry: ActivityStart, end: Option<DateTime<Local>>) -> Self {
-        TrackedActivity { start_entry, end }
-    }
-    pub fn new_completed(start_entry: ActivityStart, end: DateTime<Local>) -> Self {
         TrackedActivity {
             start_entry,
-            end: Some(end),
+            end,
+            paused: false,
         }
     }
+    pub fn new_completed(start_entry: ActivityStart, end: DateTime<Local>) -> Self {
+        TrackedActivity::new(start_entry, Some(end))
+    }
     pub fn new_ongoing(start_entry: ActivityStart) -> Self {
+        TrackedActivity::new(start_entry, None)
+    }
+    /// Create an activity ended by `end_entry`, if there is one
+    pub fn ended_by(start_entry: ActivityStart, end_entry: Option<&ActivityEntry>) -> Self {
         TrackedActivity {
-            start_entry,
-            end: None,
+            paused: end_entry.is_some_and(|e| e.is_pause()),
+            ..TrackedActivity::new(start_entry, end_entry.map(|e| *e.time_stamp()))
         }
     }
 
+    /// Only the last part keeps the pause, as the others end at midnight
     pub fn split_on_midnight(self, end_fallback: DateTime<Local>) -> SplitActivity {
         SplitActivity {
             current_start: Some(self.start_entry),
             end: self.end,
+            paused: self.paused,
             end_fallback,
         }
     }
@@ -82,6 +112,9 @@ impl TrackedActivity {
     pub fn wbs(&self) -> &str {
         self.start_entry.wbs()
     }
+    pub fn tags(&self) -> &[Rc<str>] {
+        self.start_entry.tags()
+    }
 }
 impl Display for TrackedActivity {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -91,15 +124,18 @@ impl Display for TrackedActivity {
             attendance_type: attendance,
             description: descr,
             wbs,
+            tags,
         } = &self.start_entry;
         let end = self.end_time().map(|s| s.to_string()).unwrap_or_default();
-        write!(f, "{start}\t{end}\t{name}\t{attendance}\t{wbs}\t{descr}")
+        write!(f, "{start}\t{end}\t{name}\t{attendance}\t{wbs}\t{descr}")?;
+        write_tags(f, tags)
     }
 }
 
 pub struct SplitActivity {
     current_start: Option<ActivityStart>,
     end: Option<DateTime<Local>>,
+    paused: bool,
     end_fallback: DateTime<Local>,
 }
 impl Iterator for SplitActivity {
@@ -120,34 +156,61 @@ impl Iterator for SplitActivity {
                 next_midnight - TimeDelta::nanoseconds(1),
             ))
         } else {
-            Some(TrackedActivity::new(start, self.end))
+            Some(TrackedActivity {
+                paused: self.paused,
+                ..TrackedActivity::new(start, self.end)
+            })
         }
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
 pub enum ActivityEntry {
     Start(ActivityStart),
     End(ActivityEnd),
 }
+impl Record for ActivityEntry {
+    const FIELDS: &'static [&'static str] = &[
+        "kind",
+        "start",
+        "activity",
+        "attendance",
+        "wbs",
+        "description",
+        "tags",
+        "end",
+        "pause",
+    ];
+}
 impl ActivityEntry {
     pub fn new_start(
+        time_stamp: DateTime<Local>,
         activity_name: &str,
         attendance_type: &str,
         wbs: &str,
         description: &str,
+        tags: &[&str],
     ) -> Self {
         ActivityEntry::Start(ActivityStart {
-            time_stamp: Local::now(),
+            time_stamp,
             activity_name: Rc::from(activity_name),
             attendance_type: Rc::from(attendance_type),
             description: Rc::from(description),
             wbs: Rc::from(wbs),
+            tags: tags.iter().map(|&t| Rc::from(t)).collect(),
+        })
+    }
+    pub fn new_end(time_stamp: DateTime<Local>) -> Self {
+        ActivityEntry::End(ActivityEnd {
+            time_stamp,
+            pause: false,
         })
     }
-    pub fn new_end() -> Self {
+    pub fn new_pause(time_stamp: DateTime<Local>) -> Self {
         ActivityEntry::End(ActivityEnd {
-            time_stamp: Local::now(),
+            time_stamp,
+            pause: true,
         })
     }
     pub fn time_stamp(&self) -> &DateTime<Local> {
@@ -162,6 +225,9 @@ impl ActivityEntry {
             ActivityEntry::End(_) => None,
         }
     }
+    pub fn is_pause(&self) -> bool {
+        matches!(self, ActivityEntry::End(end) if end.pause)
+    }
 }
 impl FromStr for ActivityEntry {
     type Err = ParseEntryError;
@@ -172,12 +238,20 @@ impl FromStr for ActivityEntry {
 
         let time_stamp = DateTime::from_str(time_stamp)?;
         if activity_name == END_SENTINEL {
-            return Ok(ActivityEntry::End(ActivityEnd { time_stamp }));
+            let pause = fields.next() == Some(PAUSE_MARKER);
+            return Ok(ActivityEntry::End(ActivityEnd { time_stamp, pause }));
         }
 
         let attendance_type = fields.next().ok_or(ParseEntryError::MissingAttendance)?;
         let wbs = fields.next().ok_or(ParseEntryError::MissingWbs)?;
         let description = fields.next().unwrap_or_default();
+        let tags = fields
+            .next()
+            .unwrap_or_default()
+            .split(TAG_SEPARATOR)
+            .filter(|t| !t.is_empty())
+            .map(Rc::from)
+            .collect();
 
         Ok(ActivityEntry::Start(ActivityStart {
             time_stamp,
@@ -185,32 +259,44 @@ impl FromStr for ActivityEntry {
             attendance_type: Rc::from(attendance_type),
             description: Rc::from(description),
             wbs: Rc::from(wbs),
+            tags,
         }))
     }
 }
 impl Display for ActivityEntry {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            ActivityEntry::End(ActivityEnd { time_stamp }) => {
-                write!(f, "{time_stamp}\t{END_SENTINEL}")
-            }
+            ActivityEntry::End(ActivityEnd {
+                time_stamp,
+                pause: false,
+            }) => write!(f, "{time_stamp}\t{END_SENTINEL}"),
+            ActivityEntry::End(ActivityEnd {
+                time_stamp,
+                pause: true,
+            }) => write!(f, "{time_stamp}\t{END_SENTINEL}\t{PAUSE_MARKER}"),
             ActivityEntry::Start(ActivityStart {
                 time_stamp,
                 activity_name,
                 attendance_type,
                 description,
                 wbs,
-            }) => write!(
-                f,
-                "{time_stamp}\t{activity_name}\t{attendance_type}\t{wbs}\t{description}"
-            ),
+                tags,
+            }) => {
+                write!(
+                    f,
+                    "{time_stamp}\t{activity_name}\t{attendance_type}\t{wbs}\t{description}"
+                )?;
+                write_tags(f, tags)
+            }
         }
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct ActivityEnd {
+    #[serde(rename = "end")]
     time_stamp: DateTime<Local>,
+    pause: bool,
 }
 impl ActivityEnd {
     pub fn time_stamp(&self) -> &DateTime<Local> {
@@ -218,13 +304,17 @@ impl ActivityEnd {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct ActivityStart {
+    #[serde(rename = "start")]
     time_stamp: DateTime<Local>,
+    #[serde(rename = "activity")]
     activity_name: Rc<str>,
+    #[serde(rename = "attendance")]
     attendance_type: Rc<str>,
-    description: Rc<str>,
     wbs: Rc<str>,
+    description: Rc<str>,
+    tags: Rc<[Rc<str>]>,
 }
 impl ActivityStart {
     pub fn time_stamp(&self) -> &DateTime<Local> {
@@ -242,14 +332,40 @@ impl ActivityStart {
     pub fn wbs(&self) -> &str {
         &self.wbs
     }
+    pub fn tags(&self) -> &[Rc<str>] {
+        &self.tags
+    }
 
-    fn with_timestamp(&self, time_stamp: DateTime<Local>) -> ActivityStart {
+    pub fn with_name(&self, activity_name: &str) -> ActivityStart {
+        ActivityStart {
+            activity_name: Rc::from(activity_name),
+            ..self.clone()
+        }
+    }
+
+    pub fn with_timestamp(&self, time_stamp: DateTime<Local>) -> ActivityStart {
         ActivityStart {
             time_stamp,
-            activity_name: self.activity_name.clone(),
-            attendance_type: self.attendance_type.clone(),
-            description: self.description.clone(),
-            wbs: self.wbs.clone(),
+            ..self.clone()
         }
     }
 }
+
+/// Parse a tag given on the command line, a leading `+` is optional
+pub fn parse_tag(input: &str) -> color_eyre::Result<String> {
+    let tag = input.strip_prefix('+').unwrap_or(input);
+    if tag.is_empty() || tag.contains(|c: char| c.is_whitespace() || c == TAG_SEPARATOR) {
+        return Err(color_eyre::eyre::format_err!(
+            "expected a tag without whitespace or '{TAG_SEPARATOR}', got '{input}'"
+        ));
+    }
+    Ok(tag.to_string())
+}
+
+/// Append `tags` as an extra field, leaving lines without tags unchanged
+fn write_tags(f: &mut std::fmt::Formatter<'_>, tags: &[Rc<str>]) -> std::fmt::Result {
+    if tags.is_empty() {
+        return Ok(());
+    }
+    write!(f, "\t{}", tags.join(&TAG_SEPARATOR.to_string()))
+}
@@ -1,21 +1,37 @@
 use std::{collections::HashMap, fmt::Display, rc::Rc};
 
 use chrono::{DateTime, Local, NaiveDate, TimeDelta};
+use serde::{Serialize, Serializer};
 
-use crate::activity_entry::TrackedActivity;
+use crate::{activity_entry::TrackedActivity, config::AttendanceRules, machine_readable::Record};
 
 /// Grouping of activities with
 /// - Same wbs
 /// - Same description
 /// - Same attendance type
 /// - Same local date (precise time is irrelevant)
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct CollapsedActivity {
-    attendance_type: Rc<str>,
-    description: Rc<str>,
-    duration: TimeDelta,
+    #[serde(rename = "date", serialize_with = "serialize_date")]
     start_of_first: DateTime<Local>,
+    #[serde(rename = "seconds", serialize_with = "serialize_seconds")]
+    duration: TimeDelta,
+    #[serde(rename = "attendance")]
+    attendance_type: Rc<str>,
     wbs: Rc<str>,
+    description: Rc<str>,
+    /// Union of the tags of all grouped activities
+    tags: Vec<Rc<str>>,
+}
+impl Record for CollapsedActivity {
+    const FIELDS: &'static [&'static str] = &[
+        "date",
+        "seconds",
+        "attendance",
+        "wbs",
+        "description",
+        "tags",
+    ];
 }
 impl CollapsedActivity {
     pub fn attendance(&self) -> &str {
@@ -33,6 +49,9 @@ impl CollapsedActivity {
     pub fn wbs(&self) -> &str {
         &self.wbs
     }
+    pub fn tags(&self) -> &[Rc<str>] {
+        &self.tags
+    }
 }
 impl Display for CollapsedActivity {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -47,6 +66,14 @@ impl Display for CollapsedActivity {
         )
     }
 }
+fn serialize_date<S: Serializer>(time: &DateTime<Local>, s: S) -> Result<S::Ok, S::Error> {
+    time.date_naive().serialize(s)
+}
+
+fn serialize_seconds<S: Serializer>(delta: &TimeDelta, s: S) -> Result<S::Ok, S::Error> {
+    s.serialize_f64(delta.as_seconds_f64())
+}
+
 pub fn collapse_activities(
     activities: &[TrackedActivity],
     end_fallback: DateTime<Local>,
@@ -58,7 +85,7 @@ pub fn collapse_activities(
         .flat_map(|t| t.split_on_midnight(end_fallback))
         .collect();
     for activity in &activities {
-        grouped_activities
+        let collapsed = grouped_activities
             .entry(ActivityGroupKey::from(activity))
             .or_insert_with(|| CollapsedActivity {
                 attendance_type: activity.attendance().into(),
@@ -66,9 +93,15 @@ pub fn collapse_activities(
                 duration: TimeDelta::zero(),
                 start_of_first: *activity.start_time(),
                 wbs: activity.wbs().into(),
-            })
-            .duration +=
+                tags: Vec::new(),
+            });
+        collapsed.duration +=
             activity.end_time().copied().unwrap_or(Local::now()) - activity.start_time();
+        for tag in activity.tags() {
+            if !collapsed.tags.contains(tag) {
+                collapsed.tags.push(tag.clone());
+            }
+        }
     }
 
     let mut grouped_activities = Vec::from_iter(grouped_activities.into_values());
@@ -100,15 +133,34 @@ pub struct AttendanceRange {
     attendance_type: Rc<str>,
 }
 impl AttendanceRange {
-    pub fn start_time(&self) -> &DateTime<Local> {
-        &self.start
-    }
-    pub fn end_time(&self) -> Option<&DateTime<Local>> {
-        self.end.as_ref()
-    }
     pub fn attendance(&self) -> &str {
         &self.attendance_type
     }
+    pub fn start(&self) -> DateTime<Local> {
+        self.start
+    }
+    pub fn end(&self) -> Option<DateTime<Local>> {
+        self.end
+    }
+
+    /// Apply rounding and break deductions from `rules`
+    /// Ongoing ranges are treated as ending at `end_fallback`
+    pub fn adjust(
+        &self,
+        rules: &AttendanceRules,
+        end_fallback: DateTime<Local>,
+    ) -> AdjustedAttendance {
+        let start = rules.round_start(self.start);
+        let end = self.end.map(|t| rules.round_end(t));
+        let duration = end.unwrap_or(rules.round_end(end_fallback)) - start;
+        let adjusted_duration = duration - rules.break_deduction(duration);
+        AdjustedAttendance {
+            start,
+            end,
+            duration,
+            adjusted_duration,
+        }
+    }
 }
 impl Display for AttendanceRange {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -122,6 +174,47 @@ impl Display for AttendanceRange {
     }
 }
 
+/// Rounded attendance range, as derived by [`AttendanceRange::adjust`]
+pub struct AdjustedAttendance {
+    pub start: DateTime<Local>,
+    pub end: Option<DateTime<Local>>,
+    pub duration: TimeDelta,
+    pub adjusted_duration: TimeDelta,
+}
+
+/// Time between pausing an activity and tracking the next one
+pub struct Break {
+    pub start: DateTime<Local>,
+    pub end: Option<DateTime<Local>>,
+}
+impl Break {
+    pub fn duration(&self, end_fallback: DateTime<Local>) -> TimeDelta {
+        self.end.unwrap_or(end_fallback) - self.start
+    }
+}
+
+/// Find breaks following paused activities
+/// A pause that isn't followed by another activity on the same day ends the day instead,
+/// unless it is still ongoing today
+pub fn get_breaks(activities: &[TrackedActivity], now: DateTime<Local>) -> Vec<Break> {
+    activities
+        .iter()
+        .zip(activities.iter().map(Some).skip(1).chain([None]))
+        .filter(|(activity, _)| activity.paused)
+        .filter_map(|(activity, next)| {
+            let start = *activity.end_time()?;
+            match next.map(|n| *n.start_time()) {
+                Some(end) if end.date_naive() == start.date_naive() => Some(Break {
+                    start,
+                    end: Some(end),
+                }),
+                None if now.date_naive() == start.date_naive() => Some(Break { start, end: None }),
+                _ => None,
+            }
+        })
+        .collect()
+}
+
 pub fn get_attendance_ranges(activities: &[TrackedActivity]) -> Vec<AttendanceRange> {
     let mut ranges = Vec::new();
     let mut last_range: Option<AttendanceRange> = None;
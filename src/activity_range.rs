@@ -1,20 +1,32 @@
 use std::str::FromStr;
 
-use chrono::{DateTime, Datelike, Local, NaiveDate, NaiveTime, TimeDelta, Timelike};
+use chrono::{
+    DateTime, Datelike, Days, Local, Months, NaiveDate, NaiveTime, TimeDelta, Timelike, Weekday,
+};
 use color_eyre::{
     Report,
     eyre::{Result, format_err},
 };
-use nom::{IResult, Parser, bytes::complete::take_while1};
+use nom::{
+    IResult, Parser,
+    branch::alt,
+    bytes::complete::{tag, take_while1},
+    combinator::{all_consuming, rest},
+    sequence::{preceded, separated_pair},
+};
 
 #[derive(Debug, Clone)]
 pub enum ActivityRange {
     Count(i64),
     Timeframe(InLast),
+    Span(DateSpan),
 }
 impl FromStr for ActivityRange {
     type Err = Report;
     fn from_str(input: &str) -> Result<Self, Self::Err> {
+        if let Ok((_, span)) = all_consuming(parse_date_span).parse(input) {
+            return check_span_order(span).map(ActivityRange::Span);
+        }
         let (postfix, number) = nom::combinator::opt(parse_number)
             .map(|o| o.unwrap_or_default())
             .parse(input)
@@ -25,11 +37,110 @@ impl FromStr for ActivityRange {
             "d" | "day" | "days" => Ok(ActivityRange::Timeframe(InLast::Days(number))),
             "w" | "week" | "weeks" => Ok(ActivityRange::Timeframe(InLast::Weeks(number))),
             "m" | "month" | "months" => Ok(ActivityRange::Timeframe(InLast::Months(number))),
+            _ if postfix.starts_with('-') => {
+                Err(format_err!("Invalid date, month or week '{input}'"))
+            }
             _ => Err(format_err!("Invalid postfix '{postfix}'")),
         }
     }
 }
 
+/// A range of whole days with a start and an optional end
+#[derive(Debug, Clone)]
+pub enum DateSpan {
+    /// From the first to the last date, both inclusive
+    ///
+    /// Parsed spans always end before the last representable date, so their end can be computed
+    Days(NaiveDate, NaiveDate),
+    Yesterday,
+    LastWeek,
+    LastMonth,
+    /// Since the most recent occurence of a weekday, including today
+    SinceWeekday(Weekday),
+    SinceDate(NaiveDate),
+}
+impl DateSpan {
+    /// All days of the month containing `date`
+    pub fn month_of(date: NaiveDate) -> Self {
+        let first = date.with_day(1).unwrap();
+        DateSpan::Days(first, first + Months::new(1) - Days::new(1))
+    }
+
+    /// Get the start and the exclusive end of this span
+    /// Spans reaching up to now don't have an end
+    pub fn bounds(
+        &self,
+        now: &DateTime<Local>,
+    ) -> Result<(DateTime<Local>, Option<DateTime<Local>>)> {
+        let (first, after_last) = self.first_and_after_last(now.date_naive())?;
+        let start = start_of_day(first)?;
+        let end = after_last.map(start_of_day).transpose()?;
+        Ok((start, end))
+    }
+
+    /// Get the first and the last day of this span, spans reaching up to now end `today`
+    pub fn days(&self, today: NaiveDate) -> Result<(NaiveDate, NaiveDate)> {
+        let (first, after_last) = self.first_and_after_last(today)?;
+        let last = after_last.map_or(today, |d| d - Days::new(1));
+        Ok((first, last))
+    }
+
+    fn first_and_after_last(&self, today: NaiveDate) -> Result<(NaiveDate, Option<NaiveDate>)> {
+        match self {
+            DateSpan::Days(first, last) => {
+                let after_last = last
+                    .checked_add_days(Days::new(1))
+                    .ok_or_else(|| format_err!("the day after {last} can't be represented"))?;
+                Ok((*first, Some(after_last)))
+            }
+            DateSpan::Yesterday => Ok((today - Days::new(1), Some(today))),
+            DateSpan::LastWeek => {
+                let monday = today.week(Weekday::Mon).first_day();
+                Ok((monday - Days::new(7), Some(monday)))
+            }
+            DateSpan::LastMonth => {
+                let first = today.with_day(1).unwrap();
+                Ok((first - Months::new(1), Some(first)))
+            }
+            DateSpan::SinceWeekday(weekday) => Ok((today.week(*weekday).first_day(), None)),
+            DateSpan::SinceDate(date) => Ok((*date, None)),
+        }
+    }
+}
+
+/// Parse a span of days like `2026-09-14..2026-09-18`, `2026-W38` or `since monday`
+pub fn parse_date_span_arg(input: &str) -> Result<DateSpan> {
+    let (_, span) = all_consuming(parse_date_span).parse(input).map_err(|_| {
+        format_err!("expected days like YYYY-MM-DD or YYYY-MM-DD..YYYY-MM-DD, got '{input}'")
+    })?;
+    check_span_order(span)
+}
+
+/// Fail for reversed ranges like `2026-10-15..2026-10-01`, which would contain no days
+fn check_span_order(span: DateSpan) -> Result<DateSpan> {
+    match span {
+        DateSpan::Days(first, last) if first > last => {
+            Err(format_err!("the start {first} is after the end {last}"))
+        }
+        span => Ok(span),
+    }
+}
+
+/// Parse a single month like `2026-09` into a span of its days
+pub fn parse_month_span(input: &str) -> Result<DateSpan> {
+    all_consuming(parse_month)
+        .parse(input)
+        .map(|(_, span)| span)
+        .map_err(|_| format_err!("expected a month like YYYY-MM, got '{input}'"))
+}
+
+fn start_of_day(date: NaiveDate) -> Result<DateTime<Local>> {
+    date.and_time(NaiveTime::MIN)
+        .and_local_timezone(Local)
+        .earliest()
+        .ok_or_else(|| format_err!("the start of {date} does not exist in the local timezone"))
+}
+
 #[derive(Debug, Clone)]
 pub enum InLast {
     Hours(i64),
@@ -76,3 +187,155 @@ fn parse_number(input: &str) -> IResult<&str, i64> {
         .map_res(|s: &str| s.parse::<i64>())
         .parse(input)
 }
+
+fn parse_date_span(input: &str) -> IResult<&str, DateSpan> {
+    alt((
+        preceded(tag("since "), parse_weekday).map(DateSpan::SinceWeekday),
+        preceded(tag("since "), parse_date).map(DateSpan::SinceDate),
+        separated_pair(parse_date, tag(".."), parse_date).map_opt(|(a, b)| days_span(a, b)),
+        parse_iso_week,
+        parse_date.map_opt(|d| days_span(d, d)),
+        parse_month,
+        tag("yesterday").map(|_| DateSpan::Yesterday),
+        tag("last-week").map(|_| DateSpan::LastWeek),
+        tag("last-month").map(|_| DateSpan::LastMonth),
+    ))
+    .parse(input)
+}
+
+fn parse_date(input: &str) -> IResult<&str, NaiveDate> {
+    (parse_number, tag("-"), parse_number, tag("-"), parse_number)
+        .map_opt(|(y, _, m, _, d)| {
+            NaiveDate::from_ymd_opt(
+                i32::try_from(y).ok()?,
+                u32::try_from(m).ok()?,
+                u32::try_from(d).ok()?,
+            )
+        })
+        .parse(input)
+}
+
+fn parse_month(input: &str) -> IResult<&str, DateSpan> {
+    (parse_number, tag("-"), parse_number)
+        .map_opt(|(y, _, m)| {
+            let first = NaiveDate::from_ymd_opt(i32::try_from(y).ok()?, u32::try_from(m).ok()?, 1)?;
+            let last = first
+                .checked_add_months(Months::new(1))?
+                .checked_sub_days(Days::new(1))?;
+            days_span(first, last)
+        })
+        .parse(input)
+}
+
+fn parse_iso_week(input: &str) -> IResult<&str, DateSpan> {
+    separated_pair(parse_number, tag("-W"), parse_number)
+        .map_opt(|(y, w)| {
+            let monday = NaiveDate::from_isoywd_opt(
+                i32::try_from(y).ok()?,
+                u32::try_from(w).ok()?,
+                Weekday::Mon,
+            )?;
+            days_span(monday, monday.checked_add_days(Days::new(6))?)
+        })
+        .parse(input)
+}
+
+/// Only accept spans whose end, the day after the last, can be represented
+fn days_span(first: NaiveDate, last: NaiveDate) -> Option<DateSpan> {
+    last.checked_add_days(Days::new(1))?;
+    Some(DateSpan::Days(first, last))
+}
+
+fn parse_weekday(input: &str) -> IResult<&str, Weekday> {
+    rest.map_res(Weekday::from_str).parse(input)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    fn days(span: DateSpan) -> (NaiveDate, NaiveDate) {
+        span.days(date(2026, 10, 18)).unwrap()
+    }
+
+    #[test]
+    fn parses_months_with_their_last_day() {
+        let (_, span) = parse_month("2026-02").unwrap();
+        assert_eq!(days(span), (date(2026, 2, 1), date(2026, 2, 28)));
+        let (_, span) = parse_month("2024-02").unwrap();
+        assert_eq!(days(span), (date(2024, 2, 1), date(2024, 2, 29)));
+        let (_, span) = parse_month("2026-12").unwrap();
+        assert_eq!(days(span), (date(2026, 12, 1), date(2026, 12, 31)));
+    }
+
+    #[test]
+    fn rejects_invalid_and_unrepresentable_months() {
+        for month in [
+            "2026-00",
+            "2026-13",
+            "2026-4294967297",
+            "4294969322-01",
+            "262142-12",
+        ] {
+            assert!(parse_month_span(month).is_err(), "{month}");
+        }
+        assert!(parse_month_span("262142-11").is_ok());
+    }
+
+    #[test]
+    fn parses_iso_weeks() {
+        let (_, span) = parse_iso_week("2026-W38").unwrap();
+        assert_eq!(days(span), (date(2026, 9, 14), date(2026, 9, 20)));
+        let (_, span) = parse_iso_week("2026-W53").unwrap();
+        assert_eq!(days(span), (date(2026, 12, 28), date(2027, 1, 3)));
+        for week in ["2026-W00", "2025-W53", "2026-W4294967334"] {
+            assert!(parse_date_span_arg(week).is_err(), "{week}");
+        }
+    }
+
+    #[test]
+    fn parses_days_and_ranges() {
+        let span = parse_date_span_arg("2026-09-14").unwrap();
+        assert_eq!(days(span), (date(2026, 9, 14), date(2026, 9, 14)));
+        let span = parse_date_span_arg("2026-09-14..2026-10-02").unwrap();
+        assert_eq!(days(span), (date(2026, 9, 14), date(2026, 10, 2)));
+        let span = parse_date_span_arg("since 2026-10-01").unwrap();
+        assert_eq!(days(span), (date(2026, 10, 1), date(2026, 10, 18)));
+        let span = parse_date_span_arg("since monday").unwrap();
+        assert_eq!(days(span), (date(2026, 10, 12), date(2026, 10, 18)));
+        let span = parse_date_span_arg("last-month").unwrap();
+        assert_eq!(days(span), (date(2026, 9, 1), date(2026, 9, 30)));
+
+        let e = parse_date_span_arg("2026-10-15..2026-10-01").unwrap_err();
+        assert!(e.to_string().contains("is after the end"), "{e}");
+    }
+
+    #[test]
+    fn rejects_invalid_and_unrepresentable_days() {
+        for span in [
+            "2026-02-30",
+            "2026-4294967297-01",
+            "2026-01-4294967297",
+            "262142-12-31",
+            "262142-12-31..262142-12-31",
+            "2026-01-01..262142-12-31",
+        ] {
+            assert!(parse_date_span_arg(span).is_err(), "{span}");
+            assert!(
+                !matches!(ActivityRange::from_str(span), Ok(ActivityRange::Span(_))),
+                "{span}"
+            );
+        }
+        assert!(parse_date_span_arg("262142-12-30").is_ok());
+    }
+
+    #[test]
+    fn unrepresentable_end_is_an_error() {
+        let max = NaiveDate::MAX;
+        assert!(DateSpan::Days(max, max).days(max).is_err());
+    }
+}
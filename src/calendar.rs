@@ -0,0 +1,272 @@
+use std::{collections::BTreeMap, fmt::Display, fs, path::Path, rc::Rc};
+
+use chrono::{Datelike, NaiveDate, TimeDelta};
+use clap::ValueEnum;
+use color_eyre::{
+    Section,
+    eyre::{Context, Result, format_err},
+};
+use serde::Deserialize;
+
+use crate::{
+    config::{CalendarConfig, TomlDate},
+    files,
+};
+
+mod ics;
+
+/// A day that was marked as not worked, even though it might be a working day
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum DayOff {
+    Vacation,
+    Sick,
+}
+impl Display for DayOff {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DayOff::Vacation => write!(f, "vacation"),
+            DayOff::Sick => write!(f, "sick"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Holiday {
+    pub date: NaiveDate,
+    pub name: Rc<str>,
+    /// Whether this holiday repeats on the same day every year after `date`
+    pub yearly: bool,
+}
+impl Holiday {
+    /// Yearly holidays on February 29 fall on February 28 in common years
+    fn falls_on(&self, date: NaiveDate) -> bool {
+        match self.yearly {
+            true => {
+                let anniversary = self
+                    .date
+                    .with_year(date.year())
+                    .or_else(|| NaiveDate::from_ymd_opt(date.year(), 2, 28));
+                date >= self.date && anniversary == Some(date)
+            }
+            false => date == self.date,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DayKind {
+    Regular,
+    Holiday(Rc<str>),
+    DayOff(DayOff),
+}
+
+/// A single day of the calendar with the hours expected to be worked on it
+#[derive(Debug, Clone)]
+pub struct Day {
+    pub date: NaiveDate,
+    pub kind: DayKind,
+    pub target: TimeDelta,
+}
+impl Day {
+    /// Short description like `Mon` or `Fri, Christmas`
+    pub fn describe(&self) -> String {
+        let weekday = self.date.format("%a");
+        match &self.kind {
+            DayKind::Regular => weekday.to_string(),
+            DayKind::Holiday(name) => format!("{weekday}, {name}"),
+            DayKind::DayOff(day_off) => format!("{weekday}, {day_off}"),
+        }
+    }
+}
+
+/// Adjusted attendance on a day compared to its target hours
+#[derive(Debug, Clone)]
+pub struct DayBalance {
+    pub day: Day,
+    /// Attendance counting as work
+    pub worked: TimeDelta,
+    /// Attendance of types that take time off the balance, like Gleitzeitausgleich
+    pub compensation: TimeDelta,
+}
+impl DayBalance {
+    pub fn delta(&self) -> TimeDelta {
+        self.worked - self.day.target
+    }
+}
+
+/// Working days, holidays and days off, as configured by the user
+pub struct Calendar {
+    config: CalendarConfig,
+    holidays: Vec<Holiday>,
+    days_off: BTreeMap<NaiveDate, DayOff>,
+}
+impl Calendar {
+    pub fn load(config: &CalendarConfig) -> Result<Self> {
+        let holidays = match &config.holidays {
+            Some(path) => {
+                let path = files::resolve_config_path(path)?;
+                read_holidays(&path)
+                    .wrap_err_with(|| format!("failed to read holidays from {path:?}"))?
+            }
+            None => Vec::new(),
+        };
+        Ok(Calendar {
+            config: config.clone(),
+            holidays,
+            days_off: read_days_off()?,
+        })
+    }
+
+    pub fn day(&self, date: NaiveDate) -> Day {
+        let holiday = self.holidays.iter().find(|h| h.falls_on(date));
+        let kind = match (self.days_off.get(&date), holiday) {
+            (_, Some(holiday)) => DayKind::Holiday(holiday.name.clone()),
+            (Some(day_off), None) => DayKind::DayOff(*day_off),
+            (None, None) => DayKind::Regular,
+        };
+        let target = match kind {
+            DayKind::Regular => self.config.target_hours.on(date.weekday()),
+            DayKind::Holiday(_) | DayKind::DayOff(_) => TimeDelta::zero(),
+        };
+        Day { date, kind, target }
+    }
+
+    /// Whether `date` is neither a holiday nor a day without target hours
+    /// Days off are still working days
+    pub fn is_working_day(&self, date: NaiveDate) -> bool {
+        let is_holiday = self.holidays.iter().any(|h| h.falls_on(date));
+        !is_holiday && self.config.target_hours.on(date.weekday()) > TimeDelta::zero()
+    }
+
+    /// All days from `first` to `last`, both inclusive
+    pub fn days(&self, first: NaiveDate, last: NaiveDate) -> impl Iterator<Item = Day> + '_ {
+        first
+            .iter_days()
+            .take_while(move |d| *d <= last)
+            .map(|d| self.day(d))
+    }
+
+    /// Compare attendance to the target hours of each day from `first` to `last`
+    /// `attendance` holds the date, attendance type and adjusted duration of attendance ranges
+    pub fn balance<'a>(
+        &self,
+        first: NaiveDate,
+        last: NaiveDate,
+        attendance: impl IntoIterator<Item = (NaiveDate, &'a str, TimeDelta)>,
+        compensation_types: &[String],
+    ) -> Vec<DayBalance> {
+        let mut per_day: BTreeMap<NaiveDate, (TimeDelta, TimeDelta)> = BTreeMap::new();
+        for (date, attendance_type, duration) in attendance {
+            let (worked, compensation) = per_day.entry(date).or_default();
+            match compensation_types.iter().any(|t| t == attendance_type) {
+                true => *compensation += duration,
+                false => *worked += duration,
+            }
+        }
+        self.days(first, last)
+            .map(|day| {
+                let (worked, compensation) = per_day.get(&day.date).copied().unwrap_or_default();
+                DayBalance {
+                    day,
+                    worked,
+                    compensation,
+                }
+            })
+            .collect()
+    }
+}
+
+/// Read holidays from an iCalendar file or from a TOML file with a `holidays` list
+fn read_holidays(path: &Path) -> Result<Vec<Holiday>> {
+    let content = fs::read_to_string(path)?;
+    match path.extension().and_then(|e| e.to_str()) {
+        Some("ics") => ics::parse_holidays(&content),
+        Some("toml") => parse_toml_holidays(&content),
+        _ => Err(format_err!("unsupported holiday file"))
+            .with_note(|| "holidays can be read from .ics or .toml files"),
+    }
+}
+
+fn parse_toml_holidays(content: &str) -> Result<Vec<Holiday>> {
+    #[derive(Deserialize)]
+    struct HolidayFile {
+        holidays: Vec<TomlHoliday>,
+    }
+    #[derive(Deserialize)]
+    struct TomlHoliday {
+        date: TomlDate,
+        name: String,
+        #[serde(default)]
+        yearly: bool,
+    }
+
+    let file: HolidayFile = toml::from_str(content)?;
+    let holidays = file
+        .holidays
+        .into_iter()
+        .map(|h| Holiday {
+            date: h.date.0,
+            name: Rc::from(h.name),
+            yearly: h.yearly,
+        })
+        .collect();
+    Ok(holidays)
+}
+
+/// Read all days marked as days off
+pub fn read_days_off() -> Result<BTreeMap<NaiveDate, DayOff>> {
+    let path = files::get_days_off_file_path()?;
+    if !fs::exists(&path)? {
+        return Ok(BTreeMap::new());
+    }
+    fs::read_to_string(&path)?
+        .lines()
+        .enumerate()
+        .map(|(i, line)| {
+            parse_day_off(line).wrap_err_with(|| format!("line {} of {path:?} is invalid", i + 1))
+        })
+        .collect()
+}
+
+/// Atomically overwrite all days off
+/// The caller must hold the lock on the days off file
+pub fn write_days_off(days_off: &BTreeMap<NaiveDate, DayOff>) -> Result<()> {
+    let content: String = days_off
+        .iter()
+        .map(|(date, day_off)| format!("{date}\t{day_off}\n"))
+        .collect();
+    files::write_atomically(&files::get_days_off_file_path()?, &content)
+}
+
+fn parse_day_off(line: &str) -> Result<(NaiveDate, DayOff)> {
+    let (date, kind) = line
+        .split_once('\t')
+        .ok_or_else(|| format_err!("expected a date and a kind separated by a tab"))?;
+    let date = NaiveDate::parse_from_str(date, "%Y-%m-%d")?;
+    let kind = DayOff::from_str(kind, true).map_err(|e| format_err!(e))?;
+    Ok((date, kind))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    #[test]
+    fn yearly_holiday_on_february_29_falls_on_february_28_in_common_years() {
+        let holiday = Holiday {
+            date: date(2024, 2, 29),
+            name: Rc::from("Leap day"),
+            yearly: true,
+        };
+        assert!(holiday.falls_on(date(2024, 2, 29)));
+        assert!(holiday.falls_on(date(2025, 2, 28)));
+        assert!(!holiday.falls_on(date(2025, 3, 1)));
+        assert!(holiday.falls_on(date(2028, 2, 29)));
+        assert!(!holiday.falls_on(date(2028, 2, 28)));
+        assert!(!holiday.falls_on(date(2023, 2, 28)));
+    }
+}
@@ -0,0 +1,180 @@
+use std::rc::Rc;
+
+use chrono::{Days, NaiveDate};
+use color_eyre::eyre::{Result, format_err};
+
+use super::Holiday;
+
+/// Read the events of an iCalendar file as holidays
+/// Events lasting several days become one holiday per day,
+/// events repeating with `RRULE:FREQ=YEARLY` apply every year after their start
+pub fn parse_holidays(content: &str) -> Result<Vec<Holiday>> {
+    let mut holidays = Vec::new();
+    let mut event: Option<Event> = None;
+    for line in unfold_lines(content) {
+        let Some((name, value)) = line.split_once(':') else {
+            continue;
+        };
+        // Properties can have parameters like `DTSTART;VALUE=DATE`
+        let name = name.split(';').next().unwrap_or_default();
+        match (name, &mut event) {
+            ("BEGIN", None) if value == "VEVENT" => event = Some(Event::default()),
+            ("END", Some(_)) if value == "VEVENT" => {
+                if let Some(e) = event.take() {
+                    holidays.extend(e.into_holidays()?);
+                }
+            }
+            ("DTSTART", Some(e)) => e.start = Some(parse_date(value)?),
+            ("DTEND", Some(e)) => e.end = Some(parse_date(value)?),
+            ("SUMMARY", Some(e)) => e.summary = Some(unescape_text(value)),
+            ("RRULE", Some(e)) => e.yearly = value.split(';').any(|p| p == "FREQ=YEARLY"),
+            _ => (),
+        }
+    }
+    Ok(holidays)
+}
+
+#[derive(Default)]
+struct Event {
+    start: Option<NaiveDate>,
+    /// The day after the event, like in the file
+    end: Option<NaiveDate>,
+    summary: Option<String>,
+    yearly: bool,
+}
+impl Event {
+    fn into_holidays(self) -> Result<Vec<Holiday>> {
+        let name: Rc<str> = Rc::from(self.summary.as_deref().unwrap_or("holiday"));
+        let start = self
+            .start
+            .ok_or_else(|| format_err!("the event '{name}' has no start date"))?;
+        let end = self
+            .end
+            .filter(|end| *end > start)
+            .unwrap_or(start + Days::new(1));
+        Ok(start
+            .iter_days()
+            .take_while(|d| *d < end)
+            .map(|date| Holiday {
+                date,
+                name: name.clone(),
+                yearly: self.yearly,
+            })
+            .collect())
+    }
+}
+
+/// Join lines continued by leading whitespace
+fn unfold_lines(content: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for line in content.lines() {
+        match (line.strip_prefix([' ', '\t']), lines.last_mut()) {
+            (Some(continued), Some(last)) => last.push_str(continued),
+            _ => lines.push(line.to_string()),
+        }
+    }
+    lines
+}
+
+/// Parse dates like `20261225`, ignoring the time of date-times like `20261225T000000Z`
+fn parse_date(value: &str) -> Result<NaiveDate> {
+    value
+        .get(..8)
+        .and_then(|d| NaiveDate::parse_from_str(d, "%Y%m%d").ok())
+        .ok_or_else(|| format_err!("invalid date '{value}'"))
+}
+
+fn unescape_text(value: &str) -> String {
+    let mut text = String::new();
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            text.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n' | 'N') => text.push(' '),
+            Some(escaped) => text.push(escaped),
+            None => (),
+        }
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    fn parse(events: &str) -> Vec<Holiday> {
+        let content = format!("BEGIN:VCALENDAR\r\n{events}END:VCALENDAR\r\n");
+        parse_holidays(&content).unwrap()
+    }
+
+    #[test]
+    fn single_day_event_with_date_value() {
+        let holidays = parse(
+            "BEGIN:VEVENT\r\n\
+             DTSTART;VALUE=DATE:20261225\r\n\
+             DTEND;VALUE=DATE:20261226\r\n\
+             SUMMARY:Christmas Day\r\n\
+             END:VEVENT\r\n",
+        );
+        assert_eq!(holidays.len(), 1);
+        assert_eq!(holidays[0].date, date(2026, 12, 25));
+        assert_eq!(&*holidays[0].name, "Christmas Day");
+        assert!(!holidays[0].yearly);
+    }
+
+    #[test]
+    fn folded_lines_are_joined() {
+        let holidays = parse(
+            "BEGIN:VEVENT\r\n\
+             DTSTART;VALUE=DATE:20261003\r\n\
+             SUMMARY:Day of German\r\n\x20\x20Unity\\, national\r\n\t hol\r\n iday\r\n\
+             END:VEVENT\r\n",
+        );
+        assert_eq!(&*holidays[0].name, "Day of German Unity, national holiday");
+    }
+
+    #[test]
+    fn multi_day_event_excludes_its_end() {
+        let holidays = parse(
+            "BEGIN:VEVENT\r\n\
+             DTSTART;VALUE=DATE:20261230\r\n\
+             DTEND;VALUE=DATE:20270102\r\n\
+             SUMMARY:Company holidays\r\n\
+             END:VEVENT\r\n",
+        );
+        let dates: Vec<_> = holidays.iter().map(|h| h.date).collect();
+        assert_eq!(
+            dates,
+            [date(2026, 12, 30), date(2026, 12, 31), date(2027, 1, 1)]
+        );
+    }
+
+    #[test]
+    fn yearly_rule_and_date_times() {
+        let holidays = parse(
+            "BEGIN:VEVENT\r\n\
+             DTSTART:20200501T000000Z\r\n\
+             RRULE:FREQ=YEARLY;BYMONTH=5\r\n\
+             SUMMARY:Labour Day\r\n\
+             END:VEVENT\r\n",
+        );
+        assert_eq!(holidays.len(), 1);
+        assert_eq!(holidays[0].date, date(2020, 5, 1));
+        assert!(holidays[0].yearly);
+        assert!(holidays[0].falls_on(date(2031, 5, 1)));
+        assert!(!holidays[0].falls_on(date(2019, 5, 1)));
+    }
+
+    #[test]
+    fn event_without_start_is_an_error() {
+        let content = "BEGIN:VEVENT\r\nSUMMARY:Nothing\r\nEND:VEVENT\r\n";
+        assert!(parse_holidays(content).is_err());
+    }
+}
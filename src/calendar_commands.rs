@@ -0,0 +1,96 @@
+use std::rc::Rc;
+
+use chrono::Local;
+use color_eyre::eyre::{Result, format_err};
+use owo_colors::{OwoColorize, Stream};
+
+use crate::{
+    activity_range::DateSpan,
+    calendar::{Calendar, read_days_off, write_days_off},
+    cli,
+    config::delta_to_hours,
+    files, get_existing_config, print_smart_table,
+};
+
+pub fn mark_days(mark_opts: &cli::MarkDays) -> Result<()> {
+    let (first, last) = mark_opts.days.days(Local::now().date_naive())?;
+    let _lock = files::lock_days_off_file()?;
+    let calendar = load_calendar()?;
+    let working_days = calendar
+        .days(first, last)
+        .map(|d| d.date)
+        .filter(|d| calendar.is_working_day(*d))
+        .collect::<Vec<_>>();
+    if working_days.is_empty() {
+        return Err(format_err!(
+            "there are no working days between {first} and {last}"
+        ));
+    }
+
+    let mut days_off = read_days_off()?;
+    for date in &working_days {
+        days_off.insert(*date, mark_opts.kind);
+    }
+    write_days_off(&days_off)?;
+    println!(
+        "Marked {} days between {first} and {last} as {}",
+        working_days.len(),
+        mark_opts
+            .kind
+            .if_supports_color(Stream::Stdout, |n| n.green())
+    );
+    Ok(())
+}
+
+pub fn unmark_days(unmark_opts: &cli::UnmarkDays) -> Result<()> {
+    let (first, last) = unmark_opts.days.days(Local::now().date_naive())?;
+    let _lock = files::lock_days_off_file()?;
+    let mut days_off = read_days_off()?;
+    let count = days_off.len();
+    days_off.retain(|date, _| *date < first || *date > last);
+    let unmarked = count - days_off.len();
+    if unmarked == 0 {
+        println!("No days between {first} and {last} were marked");
+        return Ok(());
+    }
+    write_days_off(&days_off)?;
+    println!(
+        "Unmarked {} days between {first} and {last}",
+        unmarked.if_supports_color(Stream::Stdout, |n| n.red())
+    );
+    Ok(())
+}
+
+pub fn list_days(list_opts: &cli::ListDays) -> Result<()> {
+    let today = Local::now().date_naive();
+    let (first, last) = list_opts
+        .days
+        .clone()
+        .unwrap_or(DateSpan::month_of(today))
+        .days(today)?;
+    let calendar = load_calendar()?;
+
+    let mut col_date: Vec<Rc<str>> = Vec::new();
+    let mut col_day: Vec<Rc<str>> = Vec::new();
+    let mut col_target: Vec<Rc<str>> = Vec::new();
+    for day in calendar.days(first, last) {
+        let target = delta_to_hours(day.target);
+        col_date.push(day.date.format("%Y-%m-%d").to_string().into());
+        col_day.push(day.describe().into());
+        col_target.push(format!("{target:.2}").into());
+    }
+    print_smart_table! {
+        "Date" => col_date,
+        "Day" => col_day,
+        "Target Hours" => col_target,
+    }
+    Ok(())
+}
+
+/// Load the calendar, using the default working week if there is no config
+pub fn load_calendar() -> Result<Calendar> {
+    let config = get_existing_config()?
+        .map(|c| c.calendar)
+        .unwrap_or_default();
+    Calendar::load(&config)
+}
@@ -1,7 +1,16 @@
-use clap::ValueEnum;
+use std::num::NonZeroUsize;
+
+use clap::{Args, ValueEnum};
 pub use clap::{Parser, Subcommand};
+use regex::Regex;
 
-use crate::{activity_range::ActivityRange, trackable::BUILTIN_ACTIVITY_IDLE_NAME};
+use crate::{
+    activity_entry::parse_tag,
+    activity_range::{ActivityRange, DateSpan, parse_date_span_arg, parse_month_span},
+    calendar::DayOff,
+    point_in_time::{PointInTime, TimeAgo},
+    storage::StorageKind,
+};
 
 #[derive(Debug, Clone, Parser)]
 #[command(author, version, about, long_about = None)]
@@ -17,14 +26,35 @@ pub enum TtrCommand {
     Start(Start),
     #[command()]
     End(End),
+    /// Stop tracking time for a break, continue with `timetrack resume`
+    #[command()]
+    Pause(End),
+    #[command()]
+    Resume(Resume),
+    #[command()]
+    Amend(Amend),
+    #[command()]
+    Undo(Undo),
+    #[command()]
+    Redo(Redo),
     #[command()]
     Show(Show),
     #[command()]
     Edit(Edit),
     #[command()]
     Generate(Generate),
+    #[command(alias = "fsck")]
+    Check(Check),
+    #[command()]
+    Balance(Balance),
+    #[command()]
+    CheckCompliance(CheckCompliance),
     #[command(subcommand)]
     Activity(ActivityCommand),
+    #[command(subcommand)]
+    Calendar(CalendarCommand),
+    #[command()]
+    MigrateStorage(MigrateStorage),
 
     // Convenience Commands
     /// Easily generate a timetrack configuration file
@@ -47,18 +77,37 @@ pub enum ActivityCommand {
     Ls(ListActivities),
 }
 
+/// Mark vacation and sick days or list working days and holidays
+///
+/// Holidays and the hours you should work on each weekday are defined in your config
+#[derive(Debug, Clone, Subcommand)]
+#[clap(verbatim_doc_comment)]
+pub enum CalendarCommand {
+    #[command()]
+    Mark(MarkDays),
+    #[command()]
+    Unmark(UnmarkDays),
+    #[command()]
+    Ls(ListDays),
+}
+
 /// Start tracking time for a specified activity
 ///
 /// This ends tracking of the previous activity
 #[derive(Debug, Clone, Parser)]
 pub struct Start {
     /// Start tracking time for this activity
-    #[clap(default_value = BUILTIN_ACTIVITY_IDLE_NAME)]
-    pub activity: String,
+    ///
+    /// Besides the full path, an alias or the unambiguous end of a path like
+    /// `maintenance` for `project/maintenance` can be used
+    /// Without an activity, you can pick one in your terminal, otherwise idle is tracked
+    #[clap(verbatim_doc_comment)]
+    pub activity: Option<String>,
 
     /// Set the attendance type of this entry
     ///
-    /// Subsequent entries will keep using this attendance type by default
+    /// Activities can define their own default attendance type
+    /// Otherwise, subsequent entries will keep using this attendance type by default
     /// The default attendance type is defined by your config
     #[clap(short, long, verbatim_doc_comment)]
     pub attendance: Option<String>,
@@ -67,6 +116,13 @@ pub struct Start {
     #[clap(short, long)]
     pub description: Option<String>,
 
+    /// Add a tag like review or +review to this entry, can be given multiple times
+    #[clap(short, long = "tag", value_parser = parse_tag)]
+    pub tags: Vec<String>,
+
+    #[command(flatten)]
+    pub time: EntryTime,
+
     /// Pollute the terminal with output
     #[clap(short, long)]
     pub verbose: bool,
@@ -75,11 +131,74 @@ pub struct Start {
 /// Stop tracking time
 #[derive(Debug, Clone, Parser)]
 pub struct End {
+    #[command(flatten)]
+    pub time: EntryTime,
+
     /// Pollute the terminal with output
     #[clap(short, long)]
     pub verbose: bool,
 }
 
+/// Continue tracking the last activity other than idle
+///
+/// The activity is restarted with the same wbs, attendance and description
+#[derive(Debug, Clone, Parser)]
+#[clap(verbatim_doc_comment)]
+pub struct Resume {
+    #[command(flatten)]
+    pub time: EntryTime,
+
+    /// Pollute the terminal with output
+    #[clap(short, long)]
+    pub verbose: bool,
+}
+
+/// Change the most recently started entry
+///
+/// Only the given values are changed, everything else is kept
+#[derive(Debug, Clone, Parser)]
+#[clap(verbatim_doc_comment)]
+pub struct Amend {
+    /// Change the tracked activity
+    pub activity: Option<String>,
+
+    /// Change the attendance type
+    #[clap(short, long)]
+    pub attendance: Option<String>,
+
+    /// Change the description
+    #[clap(short, long)]
+    pub description: Option<String>,
+
+    /// Replace all tags, can be given multiple times
+    #[clap(short, long = "tag", value_parser = parse_tag)]
+    pub tags: Vec<String>,
+
+    #[command(flatten)]
+    pub time: EntryTime,
+
+    /// Pollute the terminal with output
+    #[clap(short, long)]
+    pub verbose: bool,
+}
+
+/// Retroactively set the time of a new entry
+///
+/// Entries before the last recorded entry are inserted in chronological order
+#[derive(Debug, Clone, Args)]
+pub struct EntryTime {
+    /// Record the entry at a specific time instead of now
+    ///
+    /// - HH:MM[:SS]               At the given time today
+    /// - YYYY-MM-DDTHH:MM[:SS]    At the given date and time
+    #[clap(verbatim_doc_comment, long, conflicts_with = "ago")]
+    pub at: Option<PointInTime>,
+
+    /// Record the entry some time ago, like 20m or 1h30m
+    #[clap(long)]
+    pub ago: Option<TimeAgo>,
+}
+
 /// Show latest tracked activity or activities
 #[derive(Debug, Clone, Parser)]
 pub struct Show {
@@ -99,12 +218,28 @@ pub struct Show {
     /// - day                Show activities from the current day
     /// - week               Show activities from the current week
     /// - month              Show activities from the current month
+    /// - yesterday          Show activities from yesterday
+    /// - last-week          Show activities from the previous week
+    /// - last-month         Show activities from the previous month
+    /// - YYYY-MM-DD         Show activities from a given day
+    /// - YYYY-MM            Show activities from a given month
+    /// - YYYY-Www           Show activities from a given ISO week
+    /// - <date>..<date>     Show activities between two days, inclusively
+    /// - since <weekday>    Show activities since the last given weekday
+    /// - since <date>       Show activities since a given day
     #[clap(verbatim_doc_comment, short, long, default_value = "0")]
     pub last: ActivityRange,
 
+    #[command(flatten)]
+    pub filter: ShowFilter,
+
     /// Print machine readable values instead of a formatted table
     #[clap(short, long)]
     pub machine_readable: bool,
+
+    /// Print records with stable field names in a given format
+    #[clap(long, conflicts_with = "machine_readable")]
+    pub format: Option<OutputFormat>,
 }
 #[derive(Debug, Clone, ValueEnum)]
 pub enum ShowMode {
@@ -116,6 +251,32 @@ pub enum ShowMode {
     Attendance,
     /// Show the total tracked time, derived from selected activities
     Time,
+    /// Show the tracked time per tag, derived from selected activities
+    Tags,
+}
+
+/// Only show activities matching all given criteria
+#[derive(Debug, Clone, Args)]
+pub struct ShowFilter {
+    /// Only show this activity or activities in this category
+    #[clap(long)]
+    pub activity: Option<String>,
+
+    /// Only show activities with this wbs
+    #[clap(long)]
+    pub wbs: Option<String>,
+
+    /// Only show activities with this attendance type
+    #[clap(long)]
+    pub attendance: Option<String>,
+
+    /// Only show activities with a description matching this regular expression
+    #[clap(long)]
+    pub grep: Option<Regex>,
+
+    /// Only show activities with this tag, can be given multiple times
+    #[clap(long = "tag", value_parser = parse_tag)]
+    pub tags: Vec<String>,
 }
 
 /// Open the activity log in an editor
@@ -137,8 +298,35 @@ pub enum EditTarget {
 }
 
 /// Generate output file for a specified time frame
+///
+/// By default, the output covers the current month
 #[derive(Debug, Clone, Parser)]
+#[clap(verbatim_doc_comment)]
 pub struct Generate {
+    /// Generate output for a given month, like 2026-09
+    #[clap(long, value_parser = parse_month_span, conflicts_with_all = ["previous", "last"])]
+    pub month: Option<DateSpan>,
+
+    /// Generate output for the previous month
+    #[clap(short, long, conflicts_with = "last")]
+    pub previous: bool,
+
+    /// Generate output for a time frame, see `show --help` for possible values
+    #[clap(short, long)]
+    pub last: Option<ActivityRange>,
+
+    /// Generate daily attendance instead of activities
+    ///
+    /// This uses the [attendance_output] section of your config
+    #[clap(short, long)]
+    pub attendance: bool,
+
+    /// Use a named output profile instead of the default one
+    ///
+    /// Profiles are subtables like [output.<name>] or [attendance_output.<name>]
+    #[clap(long)]
+    pub profile: Option<String>,
+
     /// Print to stdout instead of saving to file
     #[clap(short, long)]
     pub stdout: bool,
@@ -148,6 +336,82 @@ pub struct Generate {
     pub file_path: Option<String>,
 }
 
+/// Revert the last changes made to the entry log
+///
+/// Changes made by start, end, pause, resume and amend can be undone
+#[derive(Debug, Clone, Parser)]
+#[clap(verbatim_doc_comment)]
+pub struct Undo {
+    /// The number of operations to undo
+    #[clap(default_value_t = NonZeroUsize::MIN)]
+    pub count: NonZeroUsize,
+}
+
+/// Reapply changes to the entry log that were undone
+#[derive(Debug, Clone, Parser)]
+pub struct Redo {
+    /// The number of operations to redo
+    #[clap(default_value_t = NonZeroUsize::MIN)]
+    pub count: NonZeroUsize,
+}
+
+/// Check the entry log for problems
+///
+/// Errors make the entry log unreadable and can be repaired with --fix
+/// Warnings point to entries using undefined activities or attendance types
+#[derive(Debug, Clone, Parser)]
+#[clap(verbatim_doc_comment)]
+pub struct Check {
+    /// Sort and deduplicate entries and move broken lines to a quarantine file
+    /// This can't be undone and clears the history of `timetrack undo`
+    #[clap(short, long)]
+    pub fix: bool,
+}
+
+/// Show your flextime balance, month by month
+///
+/// Adjusted attendance hours are compared to the target hours of each day,
+/// starting from `balance.since` with `balance.opening_hours` in your config.
+/// Attendance of `balance.compensation_types` (like 0900) takes time off the balance.
+#[derive(Debug, Clone, Parser)]
+#[clap(verbatim_doc_comment)]
+pub struct Balance {
+    /// Count today, even though it might not be over yet
+    #[clap(short, long)]
+    pub include_today: bool,
+
+    /// Only print the final balance in hours
+    #[clap(short, long)]
+    pub machine_readable: bool,
+}
+
+/// Check attendance against working time rules
+///
+/// Checks the maximum daily hours, rest between days, breaks and work on sundays
+/// according to `compliance` in your config, which defaults to Austrian law.
+#[derive(Debug, Clone, Parser)]
+#[clap(verbatim_doc_comment)]
+pub struct CheckCompliance {
+    /// The days to check, like 2026-09 or 2026-W38, the current month by default
+    #[clap(value_parser = parse_date_span_arg)]
+    pub days: Option<DateSpan>,
+}
+
+/// Copy all entries and activities to another storage backend
+///
+/// The configured storage is left untouched.
+/// Set `storage` in your config file afterwards to switch to the new backend.
+#[derive(Debug, Clone, Parser)]
+#[clap(verbatim_doc_comment)]
+pub struct MigrateStorage {
+    /// The storage backend to migrate to
+    pub to: StorageKind,
+
+    /// Overwrite any data already present in the target storage
+    #[clap(short, long)]
+    pub force: bool,
+}
+
 /// Define a new trackable activity
 #[derive(Debug, Clone, Parser)]
 pub struct SetActivity {
@@ -158,10 +422,23 @@ pub struct SetActivity {
     pub wbs: String,
 
     /// The default description for this activity
-    #[clap(short, long)]
+    ///
+    /// It can contain $date, $weekday, $year, $month, $day, $activity and the
+    /// current git $branch, use $$ for a literal $
+    /// Variables can be filtered like ${branch|upper} or ${date|fmt:%d.%m.}
+    #[clap(short, long, verbatim_doc_comment)]
     pub description: Option<String>,
 
+    /// The attendance type to use for this activity unless another one is given
+    #[clap(long)]
+    pub attendance: Option<String>,
+
+    /// Add an alternative name to start this activity with, can be given multiple times
+    #[clap(short, long = "alias")]
+    pub aliases: Vec<String>,
+
     /// Allow overwriting existing activities
+    /// Their aliases and attendance type are kept unless new ones are given
     #[clap(short, long)]
     pub force: bool,
 }
@@ -181,9 +458,10 @@ pub struct RemoveActivity {
     pub recursive: bool,
 }
 
-/// Rename a specified trackable activity
+/// Rename a specified trackable activity or activity category
 ///
 /// Existing entries using this activity will retain the old name
+/// unless --rewrite-history is set
 #[derive(Debug, Clone, Parser)]
 #[clap(verbatim_doc_comment)]
 pub struct MoveActivity {
@@ -192,6 +470,11 @@ pub struct MoveActivity {
 
     /// The new name of the activity
     pub to: String,
+
+    /// Also rename the activity in all recorded entries
+    /// This can't be undone and clears the history of `timetrack undo`
+    #[clap(long)]
+    pub rewrite_history: bool,
 }
 
 /// List all trackable activities
@@ -211,6 +494,40 @@ pub struct ListActivities {
     /// Print machine readable values instead of a formatted table
     #[clap(short, long)]
     pub machine_readable: bool,
+
+    /// Print records with stable field names in a given format
+    #[clap(long, conflicts_with = "machine_readable")]
+    pub format: Option<OutputFormat>,
+}
+
+/// Mark working days as days off, which don't count towards your target hours
+///
+/// Weekends and holidays are skipped
+#[derive(Debug, Clone, Parser)]
+#[clap(verbatim_doc_comment)]
+pub struct MarkDays {
+    /// The kind of day off
+    pub kind: DayOff,
+
+    /// The days to mark, like 2026-09-14, 2026-09-14..2026-09-18 or 2026-W38
+    #[clap(value_parser = parse_date_span_arg)]
+    pub days: DateSpan,
+}
+
+/// Remove marks from days off
+#[derive(Debug, Clone, Parser)]
+pub struct UnmarkDays {
+    /// The days to unmark, like 2026-09-14, 2026-09-14..2026-09-18 or 2026-W38
+    #[clap(value_parser = parse_date_span_arg)]
+    pub days: DateSpan,
+}
+
+/// List days with their target hours, holidays and days off
+#[derive(Debug, Clone, Parser)]
+pub struct ListDays {
+    /// The days to list, like 2026-09 or 2026-W38, the current month by default
+    #[clap(value_parser = parse_date_span_arg)]
+    pub days: Option<DateSpan>,
 }
 
 /// Print out configured attendance types
@@ -219,4 +536,20 @@ pub struct ListAttendanceTypes {
     /// Print machine readable values instead of a formatted list
     #[clap(short, long)]
     pub machine_readable: bool,
+
+    /// Print records with stable field names in a given format
+    #[clap(long, conflicts_with = "machine_readable")]
+    pub format: Option<OutputFormat>,
+}
+
+#[derive(Debug, Clone, ValueEnum)]
+pub enum OutputFormat {
+    /// A single JSON array of records
+    Json,
+    /// One JSON record per line
+    Jsonl,
+    /// Comma separated values with a header line
+    Csv,
+    /// Tab separated values with a header line
+    Tsv,
 }
@@ -0,0 +1,279 @@
+use std::{collections::BTreeMap, fmt::Display};
+
+use chrono::{DateTime, Datelike, Local, NaiveDate, NaiveTime, TimeDelta, Weekday};
+
+use crate::{
+    activity_entry::activity_groupings::AttendanceRange,
+    config::{ComplianceRules, delta_to_hours},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Rule {
+    DailyHours,
+    Rest,
+    Break,
+    Sunday,
+}
+impl Display for Rule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Rule::DailyHours => write!(f, "daily hours"),
+            Rule::Rest => write!(f, "rest"),
+            Rule::Break => write!(f, "break"),
+            Rule::Sunday => write!(f, "sunday"),
+        }
+    }
+}
+
+pub struct Violation {
+    pub date: NaiveDate,
+    pub rule: Rule,
+    pub message: String,
+}
+
+/// Continuous work, made up of attendance ranges with gaps too short to count as a break
+struct Block {
+    start: DateTime<Local>,
+    end: DateTime<Local>,
+}
+impl Block {
+    /// Split into parts that each lie within a single day
+    fn split_at_midnight(&self) -> Vec<Block> {
+        let mut parts = Vec::new();
+        let mut start = self.start;
+        while start.date_naive() < self.end.date_naive() {
+            let Some(next_day) = start.date_naive().succ_opt().and_then(start_of_day) else {
+                break;
+            };
+            parts.push(Block {
+                start,
+                end: next_day,
+            });
+            start = next_day;
+        }
+        if start < self.end {
+            parts.push(Block {
+                start,
+                end: self.end,
+            });
+        }
+        parts
+    }
+}
+
+/// The first time of `date`, which is after midnight if midnight falls into a daylight saving gap
+fn start_of_day(date: NaiveDate) -> Option<DateTime<Local>> {
+    let midnight = date.and_time(NaiveTime::MIN);
+    (0..24 * 60)
+        .map(|minutes| midnight + TimeDelta::minutes(minutes))
+        .find_map(|time| time.and_local_timezone(Local).earliest())
+}
+
+/// Find violations of `rules` in chronologically ordered attendance ranges
+/// Work is attributed to the day it was done on, ongoing ranges are treated as ending at `now`
+/// The rest before a day is measured from the end of the previous work to its first work
+/// Ranges of the `exempt_types` are time off and don't count as work
+pub fn find_violations(
+    ranges: &[AttendanceRange],
+    rules: &ComplianceRules,
+    exempt_types: &[String],
+    now: DateTime<Local>,
+) -> Vec<Violation> {
+    let mut blocks: Vec<Block> = Vec::new();
+    for range in ranges {
+        if exempt_types.iter().any(|t| t == range.attendance()) {
+            continue;
+        }
+        let (start, end) = (range.start(), range.end().unwrap_or(now));
+        if end <= start {
+            continue;
+        }
+        match blocks.last_mut() {
+            Some(last) if start - last.end < rules.min_break_part() => last.end = end,
+            _ => blocks.push(Block { start, end }),
+        }
+    }
+
+    let mut violations = Vec::new();
+    for pair in blocks.windows(2) {
+        let (previous, block) = (&pair[0], &pair[1]);
+        let date = block.start.date_naive();
+        let rest = block.start - previous.end;
+        if date != previous.start.date_naive() && rest < rules.min_rest() {
+            violations.push(Violation {
+                date,
+                rule: Rule::Rest,
+                message: format!(
+                    "rested {:.2} hours after the previous day, less than {:.2}",
+                    delta_to_hours(rest),
+                    rules.min_rest_hours
+                ),
+            });
+        }
+    }
+
+    let mut days: BTreeMap<NaiveDate, Vec<Block>> = BTreeMap::new();
+    for part in blocks.iter().flat_map(Block::split_at_midnight) {
+        days.entry(part.start.date_naive()).or_default().push(part);
+    }
+    for (date, blocks) in &days {
+        let mut report = |rule, message| {
+            violations.push(Violation {
+                date: *date,
+                rule,
+                message,
+            })
+        };
+        let worked: TimeDelta = blocks.iter().map(|b| b.end - b.start).sum();
+        if worked > rules.max_daily() {
+            report(
+                Rule::DailyHours,
+                format!(
+                    "worked {:.2} hours, more than {:.2}",
+                    delta_to_hours(worked),
+                    rules.max_daily_hours
+                ),
+            );
+        }
+        if worked > rules.break_after() {
+            let breaks: TimeDelta = blocks.windows(2).map(|w| w[1].start - w[0].end).sum();
+            if breaks < rules.min_break() {
+                report(
+                    Rule::Break,
+                    format!(
+                        "took {} minutes of breaks in {:.2} hours, less than {}",
+                        breaks.num_minutes(),
+                        delta_to_hours(worked),
+                        rules.min_break_minutes
+                    ),
+                );
+            }
+        }
+        if !rules.allow_sundays && date.weekday() == Weekday::Sun {
+            report(Rule::Sunday, String::from("worked on a sunday"));
+        }
+    }
+    violations.sort_by_key(|v| (v.date, v.rule));
+    violations
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+    use crate::activity_entry::{ActivityEntry, TrackedActivity, activity_groupings};
+
+    /// A time in the week from monday 2026-10-12 to sunday 2026-10-18
+    fn at(day: u32, hour: u32, minute: u32) -> DateTime<Local> {
+        Local
+            .with_ymd_and_hms(2026, 10, day, hour, minute, 0)
+            .unwrap()
+    }
+
+    fn ranges(
+        attendance: &str,
+        spans: &[(DateTime<Local>, DateTime<Local>)],
+    ) -> Vec<AttendanceRange> {
+        let activities = spans
+            .iter()
+            .map(|(start, end)| {
+                let ActivityEntry::Start(start) =
+                    ActivityEntry::new_start(*start, "work", attendance, "I.01", "", &[])
+                else {
+                    unreachable!("new_start creates a start entry")
+                };
+                TrackedActivity::new_completed(start, *end)
+            })
+            .collect::<Vec<_>>();
+        activity_groupings::get_attendance_ranges(&activities)
+    }
+
+    fn violations(ranges: &[AttendanceRange], rules: &ComplianceRules) -> Vec<(u32, Rule)> {
+        find_violations(ranges, rules, &[String::from("0900")], at(19, 0, 0))
+            .into_iter()
+            .map(|v| (v.date.day(), v.rule))
+            .collect()
+    }
+
+    #[test]
+    fn short_rest_between_days() {
+        let work = ranges(
+            "0800",
+            &[
+                (at(12, 10, 0), at(12, 13, 0)),
+                (at(12, 13, 30), at(12, 18, 0)),
+                (at(13, 4, 0), at(13, 8, 0)),
+                (at(14, 8, 0), at(14, 12, 0)),
+            ],
+        );
+        assert_eq!(
+            violations(&work, &ComplianceRules::default()),
+            [(13, Rule::Rest)]
+        );
+    }
+
+    #[test]
+    fn work_across_midnight_is_no_rest() {
+        let work = ranges(
+            "0800",
+            &[
+                (at(12, 20, 0), at(13, 2, 0)),
+                (at(13, 13, 0), at(13, 15, 0)),
+            ],
+        );
+        assert_eq!(violations(&work, &ComplianceRules::default()), []);
+    }
+
+    #[test]
+    fn missing_break() {
+        let rules = ComplianceRules::default();
+        let continuous = ranges("0800", &[(at(12, 8, 0), at(12, 14, 30))]);
+        assert_eq!(violations(&continuous, &rules), [(12, Rule::Break)]);
+
+        let too_short = ranges(
+            "0800",
+            &[
+                (at(12, 8, 0), at(12, 12, 0)),
+                (at(12, 12, 5), at(12, 14, 30)),
+            ],
+        );
+        assert_eq!(violations(&too_short, &rules), [(12, Rule::Break)]);
+
+        let taken = ranges(
+            "0800",
+            &[
+                (at(12, 8, 0), at(12, 12, 0)),
+                (at(12, 12, 30), at(12, 15, 0)),
+            ],
+        );
+        assert_eq!(violations(&taken, &rules), []);
+    }
+
+    #[test]
+    fn work_across_midnight_counts_for_both_days() {
+        let work = ranges("0800", &[(at(12, 18, 0), at(13, 8, 0))]);
+        assert_eq!(
+            violations(&work, &ComplianceRules::default()),
+            [(13, Rule::Break)]
+        );
+    }
+
+    #[test]
+    fn work_on_sundays() {
+        let work = ranges("0800", &[(at(17, 22, 0), at(18, 1, 0))]);
+        assert_eq!(
+            violations(&work, &ComplianceRules::default()),
+            [(18, Rule::Sunday)]
+        );
+
+        let allowed = ComplianceRules {
+            allow_sundays: true,
+            ..ComplianceRules::default()
+        };
+        assert_eq!(violations(&work, &allowed), []);
+
+        let time_off = ranges("0900", &[(at(18, 8, 0), at(18, 16, 0))]);
+        assert_eq!(violations(&time_off, &ComplianceRules::default()), []);
+    }
+}
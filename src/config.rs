@@ -1,8 +1,13 @@
-use std::collections::HashMap;
+use std::{
+    collections::{BTreeMap, HashMap},
+    path::PathBuf,
+};
 
-use serde::{Deserialize, Serialize};
+use chrono::{DateTime, Datelike, DurationRound, Local, NaiveDate, TimeDelta, Weekday};
+use regex::Regex;
+use serde::{Deserialize, Serialize, de::Error};
 
-use crate::format_string::FormatString;
+use crate::{format_string::FormatString, storage::StorageKind};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Config {
@@ -12,9 +17,68 @@ pub struct Config {
     pub performance_type: String,
     pub accounting_cycle: String,
     pub default_attendance: String,
+    #[serde(default)]
+    pub storage: StorageKind,
 
-    pub output: OutputConfig,
+    pub output: OutputProfiles,
+    pub attendance_output: Option<OutputProfiles>,
+    #[serde(default)]
+    pub attendance_rules: AttendanceRules,
+    #[serde(default)]
+    pub assert_format: AssertFormat,
+    #[serde(default)]
+    pub calendar: CalendarConfig,
+    #[serde(default)]
+    pub balance: BalanceConfig,
+    #[serde(default)]
+    pub compliance: ComplianceRules,
     pub attendance_types: HashMap<String, String>,
+    /// Alternative names for activities, mapping each alias to a full activity path
+    #[serde(default)]
+    pub aliases: HashMap<String, String>,
+}
+
+/// An output section, which is the default profile, with named profiles in subtables
+/// like `[output.customer]`
+#[derive(Debug, Clone, Serialize)]
+pub struct OutputProfiles {
+    #[serde(flatten)]
+    pub default: OutputConfig,
+    #[serde(flatten)]
+    pub profiles: BTreeMap<String, OutputConfig>,
+}
+impl OutputProfiles {
+    /// Get a named profile or the default one
+    pub fn get(&self, profile: Option<&str>) -> Option<&OutputConfig> {
+        match profile {
+            Some(name) => self.profiles.get(name),
+            None => Some(&self.default),
+        }
+    }
+}
+impl<'de> Deserialize<'de> for OutputProfiles {
+    /// Subtables are profiles, all other keys belong to the default profile
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let (profiles, default): (toml::Table, toml::Table) =
+            toml::Table::deserialize(deserializer)?
+                .into_iter()
+                .partition(|(_, value)| value.is_table());
+        let profiles = profiles
+            .into_iter()
+            .map(|(name, value)| {
+                let profile = OutputConfig::deserialize(value).map_err(|e| {
+                    D::Error::custom(format!("invalid output profile '{name}': {e}"))
+                })?;
+                Ok((name, profile))
+            })
+            .collect::<Result<_, D::Error>>()?;
+        let default =
+            OutputConfig::deserialize(toml::Value::Table(default)).map_err(D::Error::custom)?;
+        Ok(OutputProfiles { default, profiles })
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -25,3 +89,347 @@ pub struct OutputConfig {
     pub values: Vec<FormatString>,
     pub delimiter: String,
 }
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CalendarConfig {
+    /// Public holidays as an iCalendar `.ics` file or a `.toml` file with a `holidays` list
+    /// Relative paths are relative to the config directory
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub holidays: Option<PathBuf>,
+    pub target_hours: TargetHours,
+}
+
+/// Hours expected to be worked on each day of the week
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TargetHours {
+    #[serde(deserialize_with = "deserialize_day_hours")]
+    pub monday: f64,
+    #[serde(deserialize_with = "deserialize_day_hours")]
+    pub tuesday: f64,
+    #[serde(deserialize_with = "deserialize_day_hours")]
+    pub wednesday: f64,
+    #[serde(deserialize_with = "deserialize_day_hours")]
+    pub thursday: f64,
+    #[serde(deserialize_with = "deserialize_day_hours")]
+    pub friday: f64,
+    #[serde(deserialize_with = "deserialize_day_hours")]
+    pub saturday: f64,
+    #[serde(deserialize_with = "deserialize_day_hours")]
+    pub sunday: f64,
+}
+impl TargetHours {
+    pub fn on(&self, weekday: Weekday) -> TimeDelta {
+        let hours = match weekday {
+            Weekday::Mon => self.monday,
+            Weekday::Tue => self.tuesday,
+            Weekday::Wed => self.wednesday,
+            Weekday::Thu => self.thursday,
+            Weekday::Fri => self.friday,
+            Weekday::Sat => self.saturday,
+            Weekday::Sun => self.sunday,
+        };
+        hours_to_delta(hours)
+    }
+}
+/// Hours of a single day, which have to be between 0 and 24
+fn deserialize_day_hours<'de, D>(deserializer: D) -> Result<f64, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let hours = f64::deserialize(deserializer)?;
+    match (0.0..=24.0).contains(&hours) {
+        true => Ok(hours),
+        false => Err(D::Error::custom(format!(
+            "expected hours between 0 and 24, got {hours}"
+        ))),
+    }
+}
+impl Default for TargetHours {
+    /// A 38.5 hour week from monday to friday
+    fn default() -> Self {
+        TargetHours {
+            monday: 7.7,
+            tuesday: 7.7,
+            wednesday: 7.7,
+            thursday: 7.7,
+            friday: 7.7,
+            saturday: 0.0,
+            sunday: 0.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BalanceConfig {
+    /// The first day counting towards the flextime balance, the day of the first entry by default
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub since: Option<TomlDate>,
+    /// The balance in hours at the start of `since`
+    #[serde(deserialize_with = "deserialize_balance_hours")]
+    pub opening_hours: f64,
+    /// Attendance types that take time off the balance instead of adding to it
+    pub compensation_types: Vec<String>,
+}
+impl BalanceConfig {
+    pub fn opening(&self) -> TimeDelta {
+        hours_to_delta(self.opening_hours)
+    }
+}
+/// A balance in hours, which has to stay within a million hours either way
+fn deserialize_balance_hours<'de, D>(deserializer: D) -> Result<f64, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let hours = f64::deserialize(deserializer)?;
+    match (-1_000_000.0..=1_000_000.0).contains(&hours) {
+        true => Ok(hours),
+        false => Err(D::Error::custom(format!(
+            "expected hours between -1000000 and 1000000, got {hours}"
+        ))),
+    }
+}
+impl Default for BalanceConfig {
+    fn default() -> Self {
+        BalanceConfig {
+            since: None,
+            opening_hours: 0.0,
+            compensation_types: vec![String::from("0900")],
+        }
+    }
+}
+
+/// Limits on working time, like those of the Austrian Arbeitszeitgesetz and Arbeitsruhegesetz
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ComplianceRules {
+    #[serde(deserialize_with = "deserialize_day_hours")]
+    pub max_daily_hours: f64,
+    /// Rest required between the work of two days
+    #[serde(deserialize_with = "deserialize_day_hours")]
+    pub min_rest_hours: f64,
+    /// Days with more work than this require a break of `min_break_minutes`
+    #[serde(deserialize_with = "deserialize_day_hours")]
+    pub break_after_hours: f64,
+    pub min_break_minutes: u32,
+    /// Shorter gaps between attendance ranges don't count as a break
+    pub min_break_part_minutes: u32,
+    pub allow_sundays: bool,
+    /// Whether `show attendance` warns about violations below the table
+    pub warn_in_show: bool,
+}
+impl ComplianceRules {
+    pub fn max_daily(&self) -> TimeDelta {
+        hours_to_delta(self.max_daily_hours)
+    }
+    pub fn min_rest(&self) -> TimeDelta {
+        hours_to_delta(self.min_rest_hours)
+    }
+    pub fn break_after(&self) -> TimeDelta {
+        hours_to_delta(self.break_after_hours)
+    }
+    pub fn min_break(&self) -> TimeDelta {
+        TimeDelta::minutes(self.min_break_minutes as i64)
+    }
+    pub fn min_break_part(&self) -> TimeDelta {
+        TimeDelta::minutes(self.min_break_part_minutes as i64)
+    }
+}
+impl Default for ComplianceRules {
+    fn default() -> Self {
+        ComplianceRules {
+            max_daily_hours: 12.0,
+            min_rest_hours: 11.0,
+            break_after_hours: 6.0,
+            min_break_minutes: 30,
+            min_break_part_minutes: 10,
+            allow_sundays: false,
+            warn_in_show: false,
+        }
+    }
+}
+
+fn hours_to_delta(hours: f64) -> TimeDelta {
+    TimeDelta::seconds((hours * 3600.0).round() as i64)
+}
+
+/// Convert a time span to hours, as they are given in the config
+pub fn delta_to_hours(delta: TimeDelta) -> f64 {
+    delta.as_seconds_f64() / 3600.0
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AttendanceRules {
+    /// Attendance start and end times are rounded to multiples of this
+    pub quantum_minutes: u32,
+    pub round_start: RoundingDirection,
+    pub round_end: RoundingDirection,
+    /// The largest deduction of all exceeded thresholds applies
+    pub breaks: Vec<BreakRule>,
+}
+impl AttendanceRules {
+    pub fn round_start(&self, time: DateTime<Local>) -> DateTime<Local> {
+        self.round_start.apply(time, self.quantum())
+    }
+    pub fn round_end(&self, time: DateTime<Local>) -> DateTime<Local> {
+        self.round_end.apply(time, self.quantum())
+    }
+    pub fn break_deduction(&self, attendance: TimeDelta) -> TimeDelta {
+        self.breaks
+            .iter()
+            .filter(|b| attendance.as_seconds_f64() > b.after_hours * 3600.0)
+            .map(|b| TimeDelta::minutes(b.deduct_minutes as i64))
+            .max()
+            .unwrap_or_default()
+    }
+
+    fn quantum(&self) -> TimeDelta {
+        TimeDelta::minutes(self.quantum_minutes as i64)
+    }
+}
+impl Default for AttendanceRules {
+    fn default() -> Self {
+        AttendanceRules {
+            quantum_minutes: 15,
+            round_start: RoundingDirection::Down,
+            round_end: RoundingDirection::Up,
+            breaks: vec![BreakRule {
+                after_hours: 6.0,
+                deduct_minutes: 30,
+            }],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RoundingDirection {
+    Down,
+    Up,
+    Nearest,
+}
+impl RoundingDirection {
+    fn apply(&self, time: DateTime<Local>, quantum: TimeDelta) -> DateTime<Local> {
+        if quantum <= TimeDelta::zero() {
+            return time;
+        }
+        let rounded = match self {
+            RoundingDirection::Down => time.duration_trunc(quantum),
+            RoundingDirection::Up => time.duration_round_up(quantum),
+            RoundingDirection::Nearest => time.duration_round(quantum),
+        };
+        rounded.unwrap_or(time)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BreakRule {
+    pub after_hours: f64,
+    pub deduct_minutes: u32,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AssertFormat {
+    pub wbs: Option<FormatPattern>,
+}
+impl AssertFormat {
+    /// Check `wbs` against the configured pattern, if there is one
+    pub fn check_wbs(&self, wbs: &str) -> Result<(), String> {
+        match &self.wbs {
+            Some(pattern) if !pattern.is_match(wbs) => Err(format!(
+                "wbs '{wbs}' does not match the required format '{pattern}'"
+            )),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// A regular expression that has to match a whole value
+#[derive(Debug, Clone)]
+pub struct FormatPattern {
+    pattern: String,
+    regex: Regex,
+}
+impl FormatPattern {
+    pub fn is_match(&self, value: &str) -> bool {
+        self.regex.is_match(value)
+    }
+}
+impl std::fmt::Display for FormatPattern {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.pattern)
+    }
+}
+impl<'de> Deserialize<'de> for FormatPattern {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let pattern = String::deserialize(deserializer)?;
+        let regex = Regex::new(&format!("^(?:{pattern})$")).map_err(D::Error::custom)?;
+        Ok(FormatPattern { pattern, regex })
+    }
+}
+impl Serialize for FormatPattern {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(&self.pattern)
+    }
+}
+
+/// A date written as a TOML local date like `2026-01-01`
+#[derive(Debug, Clone, Copy)]
+pub struct TomlDate(pub NaiveDate);
+impl<'de> Deserialize<'de> for TomlDate {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let datetime = toml::value::Datetime::deserialize(deserializer)?;
+        datetime
+            .date
+            .filter(|_| datetime.time.is_none())
+            .and_then(|d| NaiveDate::from_ymd_opt(d.year as i32, d.month as u32, d.day as u32))
+            .map(TomlDate)
+            .ok_or_else(|| {
+                D::Error::custom(format!("expected a date like 2026-01-01, got {datetime}"))
+            })
+    }
+}
+impl Serialize for TomlDate {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let date = toml::value::Date {
+            year: self.0.year() as u16,
+            month: self.0.month() as u8,
+            day: self.0.day() as u8,
+        };
+        toml::value::Datetime::from(date).serialize(serializer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn partial_attendance_rules_keep_the_other_defaults() {
+        let rules: AttendanceRules = toml::from_str("quantum_minutes = 5").unwrap();
+        let defaults = AttendanceRules::default();
+        assert_eq!(rules.quantum_minutes, 5);
+        assert!(matches!(rules.round_start, RoundingDirection::Down));
+        assert!(matches!(rules.round_end, RoundingDirection::Up));
+        assert_eq!(rules.breaks.len(), defaults.breaks.len());
+
+        let rules: AttendanceRules = toml::from_str("breaks = []").unwrap();
+        assert_eq!(rules.quantum_minutes, defaults.quantum_minutes);
+        assert!(rules.breaks.is_empty());
+    }
+}
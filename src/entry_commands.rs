@@ -1,45 +1,77 @@
 use std::{
-    env, fs,
-    io::{self, Write},
+    collections::HashMap,
+    env,
     path::Path,
-    process::Command,
-    str::FromStr,
+    process::{Command, Stdio},
 };
 
 use chrono::{DateTime, Local};
 use color_eyre::{
     Section,
-    eyre::{Result, format_err},
+    eyre::{Context, Result, format_err},
     owo_colors::OwoColorize,
 };
 use owo_colors::Stream;
-use rev_lines::RawRevLines;
 
 use crate::{
-    activity_commands::get_trackable_activity,
+    activity_commands::{get_trackable_activity, pick_trackable_activity},
     activity_entry::{ActivityEntry, ActivityStart, TrackedActivity},
     cli,
+    config::Config,
     files::{self, get_activity_file_path, get_entry_file_path, get_main_config_path},
-    get_config, print_smart_list,
+    get_config, picker, print_smart_list,
+    storage::{self, StorageKind},
+    trackable::{Activity, BUILTIN_ACTIVITY_IDLE_NAME},
 };
+use journal::Change;
 
+pub use balance::show_balance;
+pub use check::check_entries;
+pub use compliance::check_compliance;
 pub use generate::handle_generate;
+pub use journal::{redo, undo};
 pub use show::show_activities;
 
+mod balance;
+mod check;
+mod compliance;
 mod generate;
+mod journal;
 mod show;
 
 pub fn start_activity(start_opts: &cli::Start) -> Result<()> {
     let config = &get_config()?;
-    let activity_name: &str = &start_opts.activity;
-    let activity = get_trackable_activity(activity_name)?;
+    let (activity, picked) = match &start_opts.activity {
+        Some(name) => (get_trackable_activity(name)?, false),
+        None if picker::is_available() => match pick_trackable_activity()? {
+            Some(activity) => (activity, true),
+            None => return Ok(()),
+        },
+        None => (get_trackable_activity(BUILTIN_ACTIVITY_IDLE_NAME)?, false),
+    };
+    check_activity_wbs(&activity, config)?;
+    let activity_name = &activity.full_path();
     let wbs = activity.wbs();
 
-    let last_entry = get_last_entry()?;
+    let time_stamp = resolve_entry_time(&start_opts.time)?;
+    let default_description = get_default_description(&activity, &time_stamp);
+    let description = match &start_opts.description {
+        Some(description) => description.clone(),
+        None if picked => match picker::edit_line("Description", &default_description)? {
+            Some(description) => description,
+            None => return Ok(()),
+        },
+        None => default_description,
+    };
+    let description = sanitize_description(&description);
+
+    let _lock = files::lock_entry_file()?;
+    let (last_entry, next_entry) = get_entries_around(&time_stamp)?;
     let last_attendance = last_entry.as_ref().and_then(|e| e.attendance_type());
     let attendance = start_opts
         .attendance
         .as_deref()
+        .or(activity.attendance())
         .or(last_attendance)
         .unwrap_or(&config.default_attendance);
     if !config.attendance_types.contains_key(attendance) {
@@ -47,15 +79,18 @@ pub fn start_activity(start_opts: &cli::Start) -> Result<()> {
             .with_note(|| "edit your config file to add a new attendance type");
     }
 
-    let description = start_opts
-        .description
-        .as_deref()
-        .or(activity.description())
-        .map(sanitize_description)
-        .unwrap_or_default();
-
-    let entry = ActivityEntry::new_start(activity_name, attendance, wbs, &description);
-    write_entry(&entry)?;
+    let tags = unique_tags(&start_opts.tags);
+    let entry = ActivityEntry::new_start(
+        time_stamp,
+        activity_name,
+        attendance,
+        wbs,
+        &description,
+        &tags,
+    );
+    journal::record("start", vec![Change::Insert(entry.clone())], || {
+        insert_entry(&entry, next_entry.is_some())
+    })?;
 
     if let Some(ActivityEntry::Start(last_start)) = last_entry.as_ref() {
         let last_name = last_start.name();
@@ -68,6 +103,9 @@ pub fn start_activity(start_opts: &cli::Start) -> Result<()> {
         "Started tracking '{}'",
         activity_name.if_supports_color(Stream::Stdout, |n| n.green())
     );
+    if let Some(next) = next_entry.as_ref() {
+        print_inserted_before(next);
+    }
 
     let timestamp = entry.time_stamp();
     if start_opts.verbose {
@@ -86,22 +124,97 @@ pub fn start_activity(start_opts: &cli::Start) -> Result<()> {
     Ok(())
 }
 
-fn sanitize_description(description: &str) -> String {
+/// Fill in the variables of the default description of `activity` for an entry at `time_stamp`
+/// Descriptions that are no valid template, like ones written before variables existed, are used as is
+fn get_default_description(activity: &Activity, time_stamp: &DateTime<Local>) -> String {
+    let Some(description) = activity.description() else {
+        return String::new();
+    };
+    let Ok(Some(template)) = activity.description_template() else {
+        return description.to_string();
+    };
+    let mut variables = HashMap::from([
+        ("date", time_stamp.format("%Y-%m-%d").to_string()),
+        ("weekday", time_stamp.format("%A").to_string()),
+        ("year", time_stamp.format("%Y").to_string()),
+        ("month", time_stamp.format("%m").to_string()),
+        ("day", time_stamp.format("%d").to_string()),
+        ("activity", activity.full_path()),
+    ]);
+    if template.uses_variable("branch") {
+        variables.insert("branch", get_current_git_branch());
+    }
+    template
+        .evaluate(&variables)
+        .unwrap_or_else(|_| description.to_string())
+}
+
+/// Name of the branch checked out in the working directory, empty outside of git repositories
+fn get_current_git_branch() -> String {
+    Command::new("git")
+        .args(["rev-parse", "--abbrev-ref", "HEAD"])
+        .stderr(Stdio::null())
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+        .unwrap_or_default()
+}
+
+/// Remove duplicate tags, keeping the order in which they were given
+fn unique_tags(tags: &[String]) -> Vec<&str> {
+    let mut unique: Vec<&str> = Vec::new();
+    for tag in tags {
+        if !unique.contains(&tag.as_str()) {
+            unique.push(tag);
+        }
+    }
+    unique
+}
+
+pub fn sanitize_description(description: &str) -> String {
     description.replace("\t", "    ").replace("\n", " -- ")
 }
 
 pub fn end_activity(end_opts: &cli::End) -> Result<()> {
-    let last_entry = get_last_entry()?;
-    match last_entry.as_ref() {
-        Some(ActivityEntry::Start(last_start)) => {
-            let entry = ActivityEntry::new_end();
-            write_entry(&entry)?;
+    stop_activity(end_opts, false)
+}
+
+pub fn pause_activity(pause_opts: &cli::End) -> Result<()> {
+    stop_activity(pause_opts, true)
+}
+
+/// End the current activity, marking the end entry as a pause if `pause` is set
+fn stop_activity(end_opts: &cli::End, pause: bool) -> Result<()> {
+    let _lock = files::lock_entry_file()?;
+    let time_stamp = resolve_entry_time(&end_opts.time)?;
+    let (last_entry, next_entry) = get_entries_around(&time_stamp)?;
+    match (last_entry.as_ref(), next_entry.as_ref()) {
+        (Some(ActivityEntry::Start(last_start)), Some(ActivityEntry::End(next_end))) => {
+            Err(format_err!(
+                "'{}' already ended at {}",
+                last_start.name(),
+                next_end.time_stamp().format("%Y-%m-%d %H:%M:%S")
+            ))
+        }
+        (Some(ActivityEntry::Start(last_start)), _) => {
+            let (entry, command) = match pause {
+                true => (ActivityEntry::new_pause(time_stamp), "pause"),
+                false => (ActivityEntry::new_end(time_stamp), "end"),
+            };
+            journal::record(command, vec![Change::Insert(entry.clone())], || {
+                insert_entry(&entry, next_entry.is_some())
+            })?;
 
             let stopped = last_start.name();
             println!(
-                "Stopped tracking '{}'",
+                "{} tracking '{}'",
+                if pause { "Paused" } else { "Stopped" },
                 stopped.if_supports_color(Stream::Stdout, |n| n.red())
             );
+            if let Some(next) = next_entry.as_ref() {
+                print_inserted_before(next);
+            }
             let timestamp = entry.time_stamp();
             if end_opts.verbose {
                 print_smart_list! {
@@ -111,30 +224,241 @@ pub fn end_activity(end_opts: &cli::End) -> Result<()> {
             }
             Ok(())
         }
+        _ if next_entry.is_some() => Err(format_err!(
+            "You were not tracking any activity at {}",
+            time_stamp.format("%Y-%m-%d %H:%M:%S")
+        )),
         _ => Err(color_eyre::eyre::format_err!(
             "You are not tracking any activity"
         )),
     }
 }
 
-fn write_entry(entry: &ActivityEntry) -> Result<()> {
-    let path = files::get_entry_file_path()?;
-    if !fs::exists(&path)?
-        && let Some(p) = path.parent()
+/// Restart the last activity other than idle with the same wbs, attendance and description
+pub fn resume_activity(resume_opts: &cli::Resume) -> Result<()> {
+    let _lock = files::lock_entry_file()?;
+    let time_stamp = resolve_entry_time(&resume_opts.time)?;
+    let (last_entry, next_entry) = get_entries_around(&time_stamp)?;
+    if let Some(ActivityEntry::Start(current)) = last_entry.as_ref()
+        && current.name() != BUILTIN_ACTIVITY_IDLE_NAME
     {
-        fs::create_dir_all(p)?
+        return Err(format_err!("You are still tracking '{}'", current.name()))
+            .with_note(|| "use `timetrack pause` to take a break first");
+    }
+
+    let storage = storage::open()?;
+    let mut resumed = None;
+    for entry in storage.entries_backwards()? {
+        if let ActivityEntry::Start(start) = entry?
+            && start.time_stamp() < &time_stamp
+            && start.name() != BUILTIN_ACTIVITY_IDLE_NAME
+        {
+            resumed = Some(start);
+            break;
+        }
     }
+    let resumed = resumed.ok_or(format_err!("There is no activity to resume"))?;
 
-    let mut file = fs::OpenOptions::new()
-        .create(true)
-        .append(true)
-        .open(path)?;
+    let entry = ActivityEntry::Start(resumed.with_timestamp(time_stamp));
+    journal::record("resume", vec![Change::Insert(entry.clone())], || {
+        insert_entry(&entry, next_entry.is_some())
+    })?;
 
-    writeln!(&mut file, "{entry}")?;
+    println!(
+        "Resumed tracking '{}'",
+        resumed
+            .name()
+            .if_supports_color(Stream::Stdout, |n| n.green())
+    );
+    if let Some(next) = next_entry.as_ref() {
+        print_inserted_before(next);
+    }
+    if resume_opts.verbose {
+        print_smart_list! {
+            "Description" => resumed.description(),
+            "Attendance" => resumed.attendance(),
+            "WBS" => resumed.wbs(),
+            "Date" => &time_stamp.format("%Y-%m-%d").to_string(),
+            "Time" => &time_stamp.format("%H:%M:%S").to_string(),
+        }
+    }
+    Ok(())
+}
+
+/// Resolve the time stamp for a new entry, defaulting to now
+fn resolve_entry_time(entry_time: &cli::EntryTime) -> Result<DateTime<Local>> {
+    let now = Local::now();
+    let time_stamp = match (&entry_time.at, &entry_time.ago) {
+        (Some(at), _) => at.resolve(&now)?,
+        (None, Some(ago)) => ago.resolve(&now)?,
+        (None, None) => return Ok(now),
+    };
+    if time_stamp > now {
+        return Err(format_err!(
+            "{} is in the future",
+            time_stamp.format("%Y-%m-%d %H:%M:%S")
+        ));
+    }
+    Ok(time_stamp)
+}
+
+/// Fetch the last entry at or before `time_stamp` and the first entry after it
+/// An existing entry at exactly `time_stamp` is considered a conflict
+fn get_entries_around(
+    time_stamp: &DateTime<Local>,
+) -> Result<(Option<ActivityEntry>, Option<ActivityEntry>)> {
+    let mut entries = get_backwards_entries_since(time_stamp)?;
+    if let Some(conflict) = entries.iter().find(|e| e.time_stamp() == time_stamp) {
+        return Err(format_err!(
+            "conflicting entry at the same time: {conflict}"
+        ))
+        .with_note(|| "use `timetrack edit` to resolve conflicts manually");
+    }
+    let split = entries.partition_point(|e| e.time_stamp() > time_stamp);
+    let last = entries.drain(split..).next();
+    Ok((last, entries.pop()))
+}
+
+fn print_inserted_before(next_entry: &ActivityEntry) {
+    let time_stamp = next_entry.time_stamp().format("%Y-%m-%d %H:%M:%S");
+    println!(
+        "Inserted before the entry at {}",
+        time_stamp.if_supports_color(Stream::Stdout, |n| n.yellow())
+    );
+}
+
+/// Write `entry` to the entry file in chronological order
+/// Appending is only possible if no recorded entry is newer than `entry`
+fn insert_entry(entry: &ActivityEntry, has_newer_entries: bool) -> Result<()> {
+    if !has_newer_entries {
+        return write_entry(entry);
+    }
+    let mut entries = get_all_entries()?;
+    let position = entries.partition_point(|e| e.time_stamp() <= entry.time_stamp());
+    entries.insert(position, entry.clone());
+    write_all_entries(&entries)
+}
+
+fn write_entry(entry: &ActivityEntry) -> Result<()> {
+    storage::open()?.append_entry(entry)
+}
+
+/// Fail if the wbs of an activity that is about to be tracked doesn't satisfy the format assertions
+fn check_activity_wbs(activity: &Activity, config: &Config) -> Result<()> {
+    if activity.full_path() == BUILTIN_ACTIVITY_IDLE_NAME {
+        return Ok(());
+    }
+    config
+        .assert_format
+        .check_wbs(activity.wbs())
+        .map_err(|e| format_err!(e))
+        .wrap_err_with(|| format!("invalid activity '{}'", activity.full_path()))
+        .with_note(|| "use `timetrack activity set --force` to fix the activity")
+}
+
+/// Rewrite all entries with changes that can't be undone, which discards the journal
+/// The caller must hold the lock on the entry file
+pub fn rewrite_history(entries: &[ActivityEntry]) -> Result<()> {
+    journal::discard()?;
+    write_all_entries(entries)
+}
+
+/// Atomically overwrite all recorded entries with `entries`
+/// The caller must hold the lock on the entry file
+pub fn write_all_entries(entries: &[ActivityEntry]) -> Result<()> {
+    storage::open()?.rewrite_entries(entries)
+}
+
+pub fn amend_entry(amend_opts: &cli::Amend) -> Result<()> {
+    let config = &get_config()?;
+    let _lock = files::lock_entry_file()?;
+    let mut entries = get_all_entries()?;
+    let index = entries
+        .iter()
+        .rposition(|e| matches!(e, ActivityEntry::Start(_)))
+        .ok_or(format_err!("You have not started any activity yet"))?;
+    let ActivityEntry::Start(old) = &entries[index] else {
+        unreachable!("index points to a start entry")
+    };
+
+    let activity = amend_opts
+        .activity
+        .as_deref()
+        .map(get_trackable_activity)
+        .transpose()?;
+    if let Some(activity) = &activity {
+        check_activity_wbs(activity, config)?;
+    }
+    let (activity_name, wbs) = match &activity {
+        Some(activity) => (activity.full_path(), activity.wbs().to_string()),
+        None => (old.name().to_string(), old.wbs().to_string()),
+    };
+    let attendance = amend_opts
+        .attendance
+        .as_deref()
+        .or(activity.as_ref().and_then(Activity::attendance))
+        .unwrap_or(old.attendance());
+    if !config.attendance_types.contains_key(attendance) {
+        return Err(format_err!("attendance type '{attendance}' is not defined"))
+            .with_note(|| "edit your config file to add a new attendance type");
+    }
+    let description = match &amend_opts.description {
+        Some(d) => sanitize_description(d),
+        None => old.description().to_string(),
+    };
+    let tags = match amend_opts.tags.is_empty() {
+        true => old.tags().iter().map(|t| t.as_ref()).collect(),
+        false => unique_tags(&amend_opts.tags),
+    };
+    let time_stamp = match (&amend_opts.time.at, &amend_opts.time.ago) {
+        (None, None) => *old.time_stamp(),
+        _ => resolve_entry_time(&amend_opts.time)?,
+    };
+
+    let previous = index.checked_sub(1).map(|i| entries[i].time_stamp());
+    let next = entries.get(index + 1).map(|e| e.time_stamp());
+    if previous.is_some_and(|t| t >= &time_stamp) || next.is_some_and(|t| t <= &time_stamp) {
+        return Err(format_err!(
+            "the start time must stay between the surrounding entries"
+        ))
+        .with_note(|| "use `timetrack edit` to reorder entries manually");
+    }
+
+    let amended = ActivityEntry::new_start(
+        time_stamp,
+        &activity_name,
+        attendance,
+        &wbs,
+        &description,
+        &tags,
+    );
+    let old = std::mem::replace(&mut entries[index], amended.clone());
+    journal::record(
+        "amend",
+        vec![Change::Remove(old.clone()), Change::Insert(amended.clone())],
+        || write_all_entries(&entries),
+    )?;
+
+    println!(
+        "Amended '{}'",
+        activity_name.if_supports_color(Stream::Stdout, |n| n.green())
+    );
+    if amend_opts.verbose {
+        print_smart_list! {
+            "Before" => old,
+            "After" => amended,
+        }
+    }
     Ok(())
 }
 
 pub fn handle_edit(edit_opts: &cli::Edit) -> Result<()> {
+    if !matches!(edit_opts.target, cli::EditTarget::Config)
+        && storage::configured_kind()? != StorageKind::Tsv
+    {
+        return Err(format_err!("only the tsv storage can be edited by hand"))
+            .with_note(|| "use `timetrack migrate-storage tsv` to export your data");
+    }
     let path = match edit_opts.target {
         cli::EditTarget::Entries => get_entry_file_path(),
         cli::EditTarget::Config => get_main_config_path(),
@@ -155,38 +479,34 @@ fn open_editor_to_file(path: &Path) -> Result<()> {
 
 /// Fetch the last recorded activity entry
 fn get_last_entry() -> Result<Option<ActivityEntry>> {
-    let path = &files::get_entry_file_path()?;
-    if !fs::exists(path)? {
-        return Ok(None);
-    }
-    let file = fs::File::open(path)?;
-    match RawRevLines::new(file).next() {
-        Some(l) => Ok(Some(entry_from_byte_result(l)?)),
-        None => Ok(None),
-    }
+    storage::open()?.last_entry()
 }
 
-/// Get the last `count` activities in chronological order
-/// Activities crossing over midnight will be automatically split
-fn get_last_n_activities(count: usize) -> Result<Vec<TrackedActivity>> {
-    let path = &files::get_entry_file_path()?;
-    if !fs::exists(path)? {
-        return Ok(Vec::new());
-    }
+/// Fetch all recorded entries in chronological order
+pub fn get_all_entries() -> Result<Vec<ActivityEntry>> {
+    storage::open()?.all_entries()
+}
 
-    let file = fs::File::open(path)?;
-    let mut rev_lines = RawRevLines::new(file);
+/// Get the last `count` activities matching `filter` in chronological order
+/// Activities crossing over midnight will be automatically split
+fn get_last_n_activities<F>(count: usize, filter: F) -> Result<Vec<TrackedActivity>>
+where
+    F: Fn(&ActivityStart) -> bool,
+{
+    let storage = storage::open()?;
+    let mut entries = storage.entries_backwards()?;
     let mut activities = Vec::new();
-    let mut last_timestamp = None;
-    while let Some(line) = rev_lines.next()
+    let mut next_entry = None;
+    while let Some(entry) = entries.next()
         && activities.len() < count
     {
-        let entry = entry_from_byte_result(line)?;
-        let end_timestamp = last_timestamp.take();
-        last_timestamp = Some(*entry.time_stamp());
-        if let ActivityEntry::Start(start_entry) = entry {
+        let entry = entry?;
+        let end_entry = next_entry.replace(entry.clone());
+        if let ActivityEntry::Start(start_entry) = entry
+            && filter(&start_entry)
+        {
             activities.extend(
-                TrackedActivity::new(start_entry, end_timestamp)
+                TrackedActivity::ended_by(start_entry, end_entry.as_ref())
                     .split_on_midnight(Local::now())
                     .collect::<Vec<_>>()
                     .into_iter()
@@ -200,15 +520,21 @@ fn get_last_n_activities(count: usize) -> Result<Vec<TrackedActivity>> {
 }
 
 /// Get activities since `start_time` in chronological order
+/// If `end_time` is given, activities are clipped to end before it
 /// Activities crossing over midnight will be automatically split
-fn get_activities_since(start_time: &DateTime<Local>) -> Result<Vec<TrackedActivity>> {
+fn get_activities_since(
+    start_time: &DateTime<Local>,
+    end_time: Option<&DateTime<Local>>,
+) -> Result<Vec<TrackedActivity>> {
+    let now = Local::now();
+    let end_time = end_time.filter(|&t| t < &now);
     let mut activities = Vec::new();
     let mut last_activity_start: Option<ActivityStart> = None;
     for entry in get_backwards_entries_since(start_time)?.into_iter().rev() {
         if let Some(last) = last_activity_start {
             activities.extend(
-                TrackedActivity::new_completed(last, *entry.time_stamp())
-                    .split_on_midnight(Local::now())
+                TrackedActivity::ended_by(last, Some(&entry))
+                    .split_on_midnight(now)
                     .filter(|a| a.end_time().map(|t| t >= start_time).unwrap_or(true)),
             );
         }
@@ -217,8 +543,24 @@ fn get_activities_since(start_time: &DateTime<Local>) -> Result<Vec<TrackedActiv
             ActivityEntry::End(_) => None,
         };
     }
-    if let Some(last) = last_activity_start {
-        activities.push(TrackedActivity::new_ongoing(last));
+    match (last_activity_start, end_time) {
+        (Some(last), Some(end)) => activities.extend(
+            TrackedActivity::new_completed(last, *end)
+                .split_on_midnight(*end)
+                .filter(|a| a.end_time().map(|t| t >= start_time).unwrap_or(true)),
+        ),
+        (Some(last), None) => activities.push(TrackedActivity::new_ongoing(last)),
+        (None, _) => (),
+    }
+
+    if let Some(end) = end_time {
+        activities.retain(|a| a.start_time() < end);
+        for activity in &mut activities {
+            if activity.end_time().is_none_or(|t| t > end) {
+                activity.end = Some(*end);
+                activity.paused = false;
+            }
+        }
     }
     Ok(activities)
 }
@@ -227,26 +569,39 @@ fn get_activities_since(start_time: &DateTime<Local>) -> Result<Vec<TrackedActiv
 /// The first entry before `start_time` will also be included
 /// This allows showing an activity that was currently running at `start_time`
 fn get_backwards_entries_since(start_time: &DateTime<Local>) -> Result<Vec<ActivityEntry>> {
-    let path = &files::get_entry_file_path()?;
-    if !fs::exists(path)? {
-        return Ok(Vec::new());
-    }
-    let mut entries = Vec::new();
-    let file = fs::File::open(path)?;
-    for line in RawRevLines::new(file) {
-        let entry = entry_from_byte_result(line)?;
-        let time = *entry.time_stamp();
-        entries.push(entry);
-        if &time <= start_time {
-            break;
-        }
-    }
-    Ok(entries)
+    storage::open()?.entries_backwards_since(start_time)
 }
 
-fn entry_from_byte_result(
-    byte_result: std::result::Result<Vec<u8>, io::Error>,
-) -> Result<ActivityEntry> {
-    let entry_str = String::from_utf8(byte_result?)?;
-    Ok(ActivityEntry::from_str(&entry_str)?)
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use chrono::TimeZone;
+
+    use super::*;
+
+    fn description_for(line: &str) -> String {
+        let activity = Activity::from_str(line).unwrap();
+        let time_stamp = Local.with_ymd_and_hms(2026, 3, 14, 9, 0, 0).unwrap();
+        get_default_description(&activity, &time_stamp)
+    }
+
+    #[test]
+    fn default_description_fills_in_variables() {
+        assert_eq!(
+            description_for("misc\tI.01\t$activity on $date"),
+            "misc on 2026-03-14"
+        );
+        assert_eq!(description_for("misc\tI.01\tPaid $$5"), "Paid $5");
+        assert_eq!(description_for("misc\tI.01\t"), "");
+    }
+
+    #[test]
+    fn legacy_default_description_with_dollar_is_literal() {
+        assert_eq!(
+            description_for("misc\tI.01\tPaid $5 for parking"),
+            "Paid $5 for parking"
+        );
+        assert_eq!(description_for("misc\tI.01\tTicket $JIRA"), "Ticket $JIRA");
+    }
 }
@@ -0,0 +1,123 @@
+use std::{collections::BTreeMap, rc::Rc};
+
+use chrono::{Datelike, Days, Local, NaiveDate, TimeDelta};
+use color_eyre::eyre::Result;
+use owo_colors::{OwoColorize, Stream};
+
+use crate::{
+    activity_entry::activity_groupings::get_attendance_ranges,
+    activity_range::DateSpan,
+    calendar::{Calendar, DayBalance},
+    cli,
+    config::{Config, delta_to_hours},
+    get_config, print_smart_table,
+};
+
+use super::{get_activities_since, get_all_entries};
+
+/// Hours summed up over a month
+#[derive(Default)]
+struct MonthBalance {
+    target: TimeDelta,
+    worked: TimeDelta,
+    compensation: TimeDelta,
+}
+
+pub fn show_balance(balance_opts: &cli::Balance) -> Result<()> {
+    let config = get_config()?;
+    let now = Local::now();
+    let first = match &config.balance.since {
+        Some(since) => since.0,
+        None => match get_all_entries()?.first() {
+            Some(entry) => entry.time_stamp().date_naive(),
+            None => {
+                println!("You have not recorded any data yet");
+                return Ok(());
+            }
+        },
+    };
+    let last = match balance_opts.include_today {
+        true => now.date_naive(),
+        false => now.date_naive() - Days::new(1),
+    };
+
+    let days = match first <= last {
+        true => get_day_balances(first, last, &config)?,
+        false => Vec::new(),
+    };
+    let mut months: BTreeMap<(i32, u32), MonthBalance> = BTreeMap::new();
+    for day in &days {
+        let month = months
+            .entry((day.day.date.year(), day.day.date.month()))
+            .or_default();
+        month.target += day.day.target;
+        month.worked += day.worked;
+        month.compensation += day.compensation;
+    }
+
+    let opening = config.balance.opening();
+    let closing = opening + days.iter().map(DayBalance::delta).sum::<TimeDelta>();
+    if balance_opts.machine_readable {
+        println!("{:.2}", delta_to_hours(closing));
+        return Ok(());
+    }
+    print_balance_table(opening, &months);
+    let closing_str = format!("{:+.2}", delta_to_hours(closing));
+    let closing_str = match closing < TimeDelta::zero() {
+        true => closing_str
+            .if_supports_color(Stream::Stdout, |n| n.red())
+            .to_string(),
+        false => closing_str
+            .if_supports_color(Stream::Stdout, |n| n.green())
+            .to_string(),
+    };
+    println!("\nBalance at the end of {last}: {closing_str} hours");
+    Ok(())
+}
+
+/// Compare the adjusted attendance on each day from `first` to `last` to its target hours
+fn get_day_balances(first: NaiveDate, last: NaiveDate, config: &Config) -> Result<Vec<DayBalance>> {
+    let now = Local::now();
+    let (start, end) = DateSpan::Days(first, last).bounds(&now)?;
+    let activities = get_activities_since(&start, end.as_ref())?;
+    let ranges = get_attendance_ranges(&activities);
+    let adjusted = ranges
+        .iter()
+        .map(|r| (r, r.adjust(&config.attendance_rules, now)))
+        .collect::<Vec<_>>();
+    let attendance = adjusted
+        .iter()
+        .map(|(r, a)| (a.start.date_naive(), r.attendance(), a.adjusted_duration));
+    let calendar = Calendar::load(&config.calendar)?;
+    Ok(calendar.balance(first, last, attendance, &config.balance.compensation_types))
+}
+
+fn print_balance_table(opening: TimeDelta, months: &BTreeMap<(i32, u32), MonthBalance>) {
+    let mut col_month: Vec<Rc<str>> = vec!["Opening".into()];
+    let mut col_target: Vec<Rc<str>> = vec!["".into()];
+    let mut col_worked: Vec<Rc<str>> = vec!["".into()];
+    let mut col_compensation: Vec<Rc<str>> = vec!["".into()];
+    let mut col_delta: Vec<Rc<str>> = vec!["".into()];
+    let mut col_balance: Vec<Rc<str>> = vec![format!("{:+.2}", delta_to_hours(opening)).into()];
+
+    let mut balance = opening;
+    for ((year, month), hours_in_month) in months {
+        let delta = hours_in_month.worked - hours_in_month.target;
+        balance += delta;
+        col_month.push(format!("{year}-{month:02}").into());
+        col_target.push(format!("{:.2}", delta_to_hours(hours_in_month.target)).into());
+        col_worked.push(format!("{:.2}", delta_to_hours(hours_in_month.worked)).into());
+        col_compensation.push(format!("{:.2}", delta_to_hours(hours_in_month.compensation)).into());
+        col_delta.push(format!("{:+.2}", delta_to_hours(delta)).into());
+        col_balance.push(format!("{:+.2}", delta_to_hours(balance)).into());
+    }
+
+    print_smart_table! {
+        "Month" => col_month,
+        "Target" => col_target,
+        "Worked" => col_worked,
+        "Compensation" => col_compensation,
+        "Delta" => col_delta,
+        "Balance" => col_balance,
+    }
+}
@@ -0,0 +1,333 @@
+use std::{collections::HashSet, fmt::Display, fs, io::Write, rc::Rc, str::FromStr};
+
+use color_eyre::{
+    Section,
+    eyre::{Result, format_err},
+};
+use owo_colors::{OwoColorize, Stream};
+
+use crate::{
+    activity_commands::get_all_trackable_activities,
+    activity_entry::ActivityEntry,
+    cli,
+    config::Config,
+    files, get_existing_config, print_smart_table,
+    storage::{self, StorageKind},
+    trackable::Activity,
+};
+
+use super::{get_all_entries, rewrite_history};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Severity {
+    /// The entry log is structurally broken and can be repaired with --fix
+    Error,
+    /// The entry log is valid, but refers to things that are undefined or malformed
+    Warning,
+}
+impl Display for Severity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Severity::Error => write!(f, "error"),
+            Severity::Warning => write!(f, "warning"),
+        }
+    }
+}
+
+/// Where a problem was found
+enum Location {
+    /// A line of the entry file, or the position of an entry in other storages
+    Entry(usize),
+    /// A line of the activity file
+    ActivityLine(usize),
+    /// An activity of storages without lines
+    Activity(String),
+    /// The activity definitions as a whole
+    Activities,
+}
+impl Display for Location {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Location::Entry(line) => write!(f, "{line}"),
+            Location::ActivityLine(line) => write!(f, "activities:{line}"),
+            Location::Activity(path) => write!(f, "activity '{path}'"),
+            Location::Activities => write!(f, "activities"),
+        }
+    }
+}
+
+struct Problem {
+    location: Location,
+    severity: Severity,
+    message: String,
+}
+
+/// A line of the entry file, which may or may not be a valid entry
+struct CheckedLine {
+    number: usize,
+    raw: String,
+    entry: std::result::Result<ActivityEntry, String>,
+}
+
+pub fn check_entries(check_opts: &cli::Check) -> Result<()> {
+    let _lock = files::lock_entry_file()?;
+    let (lines, location) = match storage::configured_kind()? {
+        StorageKind::Tsv => {
+            let path = files::get_entry_file_path()?;
+            if !fs::exists(&path)? {
+                println!("You have not recorded any data yet");
+                return Ok(());
+            }
+            (read_checked_lines()?, format!("{path:?}"))
+        }
+        kind => (read_stored_entries()?, format!("the {kind} storage")),
+    };
+    // The storage kind is configured, so a broken config has already failed the check
+    let config = get_existing_config()?;
+    let mut problems = Vec::new();
+    let activities: Option<HashSet<String>> = match get_all_trackable_activities() {
+        Ok(activities) => Some(activities.iter().map(|a| a.full_path()).collect()),
+        Err(e) => {
+            problems.push(Problem {
+                location: Location::Activities,
+                severity: Severity::Warning,
+                message: format!("failed to load, activity names were not checked: {e:#}"),
+            });
+            None
+        }
+    };
+    problems.extend(find_problems(&lines, config.as_ref(), activities.as_ref()));
+    if let Some(config) = &config {
+        problems.extend(find_activity_problems(config)?);
+    }
+    if problems.is_empty() {
+        println!("No problems found in {location}");
+        return Ok(());
+    }
+    print_problems(&problems);
+
+    let errors = problems
+        .iter()
+        .filter(|p| p.severity == Severity::Error)
+        .count();
+    if check_opts.fix && errors > 0 {
+        return fix_entries(lines);
+    }
+    match errors {
+        0 => Ok(()),
+        n => Err(format_err!("found {n} errors in {location}"))
+            .with_note(|| "use --fix to sort entries and quarantine broken lines"),
+    }
+}
+
+/// Entries of storages other than tsv can't be unparsable, so they are checked as stored
+fn read_stored_entries() -> Result<Vec<CheckedLine>> {
+    let lines = get_all_entries()?
+        .into_iter()
+        .enumerate()
+        .map(|(i, entry)| CheckedLine {
+            number: i + 1,
+            raw: entry.to_string(),
+            entry: Ok(entry),
+        })
+        .collect();
+    Ok(lines)
+}
+
+fn read_checked_lines() -> Result<Vec<CheckedLine>> {
+    let content = fs::read(files::get_entry_file_path()?)?;
+    let lines = content
+        .split(|b| *b == b'\n')
+        .enumerate()
+        .filter(|(_, line)| !line.is_empty())
+        .map(|(i, line)| {
+            let raw = String::from_utf8_lossy(line).into_owned();
+            let entry = std::str::from_utf8(line)
+                .map_err(|_| String::from("invalid utf-8"))
+                .and_then(|l| ActivityEntry::from_str(l).map_err(|e| e.to_string()));
+            CheckedLine {
+                number: i + 1,
+                raw,
+                entry,
+            }
+        })
+        .collect();
+    Ok(lines)
+}
+
+fn find_problems(
+    lines: &[CheckedLine],
+    config: Option<&Config>,
+    activities: Option<&HashSet<String>>,
+) -> Vec<Problem> {
+    let mut problems = Vec::new();
+    let mut seen = HashSet::new();
+    let mut last: Option<&ActivityEntry> = None;
+    for line in lines {
+        let mut report = |severity, message: String| {
+            problems.push(Problem {
+                location: Location::Entry(line.number),
+                severity,
+                message,
+            })
+        };
+        let entry = match &line.entry {
+            Ok(entry) => entry,
+            Err(reason) => {
+                report(Severity::Error, format!("unparsable line: {reason}"));
+                continue;
+            }
+        };
+
+        let duplicate = !seen.insert(entry.to_string());
+        if duplicate {
+            report(Severity::Error, String::from("duplicate entry"));
+        }
+        if last.is_none() && matches!(entry, ActivityEntry::End(_)) {
+            report(
+                Severity::Error,
+                String::from("end entry without a started activity"),
+            );
+        }
+        if let Some(previous) = last {
+            if entry.time_stamp() < previous.time_stamp() {
+                report(Severity::Error, String::from("time stamp is out of order"));
+            }
+            if entry.time_stamp() == previous.time_stamp() && !duplicate {
+                report(
+                    Severity::Warning,
+                    String::from("same time stamp as the previous entry"),
+                );
+            }
+            if let (ActivityEntry::End(_), ActivityEntry::End(_)) = (previous, entry) {
+                report(Severity::Error, String::from("consecutive end entries"));
+            }
+        }
+        if let ActivityEntry::Start(start) = entry {
+            if let Some(config) = config
+                && !config.attendance_types.contains_key(start.attendance())
+            {
+                let attendance = start.attendance();
+                report(
+                    Severity::Warning,
+                    format!("unknown attendance type '{attendance}'"),
+                );
+            }
+            if let Some(activities) = activities
+                && !activities.contains(start.name())
+            {
+                let name = start.name();
+                report(Severity::Warning, format!("undefined activity '{name}'"));
+            }
+        }
+        last = Some(entry);
+    }
+    problems
+}
+
+/// Find defined activities whose wbs doesn't satisfy the format assertions
+/// Unparsable activities are skipped, they are reported as a failure to load all activities
+fn find_activity_problems(config: &Config) -> Result<Vec<Problem>> {
+    let activities: Vec<_> = match storage::configured_kind()? {
+        StorageKind::Tsv => {
+            let path = files::get_activity_file_path()?;
+            if !fs::exists(&path)? {
+                return Ok(Vec::new());
+            }
+            fs::read_to_string(&path)?
+                .lines()
+                .enumerate()
+                .filter(|(_, line)| !line.trim().is_empty())
+                .filter_map(|(i, line)| {
+                    Some((
+                        Location::ActivityLine(i + 1),
+                        Activity::from_str(line).ok()?,
+                    ))
+                })
+                .collect()
+        }
+        _ => storage::open()?
+            .activities()
+            .unwrap_or_default()
+            .into_iter()
+            .map(|a| (Location::Activity(a.full_path()), a))
+            .collect(),
+    };
+    let problems = activities
+        .into_iter()
+        .filter_map(|(location, activity)| {
+            let e = config.assert_format.check_wbs(activity.wbs()).err()?;
+            Some(Problem {
+                location,
+                severity: Severity::Warning,
+                message: format!("activity '{}': {e}", activity.full_path()),
+            })
+        })
+        .collect();
+    Ok(problems)
+}
+
+fn print_problems(problems: &[Problem]) {
+    let mut col_location: Vec<Rc<str>> = Vec::new();
+    let mut col_severity: Vec<Rc<str>> = Vec::new();
+    let mut col_problem: Vec<Rc<str>> = Vec::new();
+    for problem in problems {
+        col_location.push(problem.location.to_string().into());
+        col_severity.push(problem.severity.to_string().into());
+        col_problem.push(problem.message.as_str().into());
+    }
+    print_smart_table! {
+        "Location" => col_location,
+        "Severity" => col_severity,
+        "Problem" => col_problem,
+    }
+}
+
+/// Sort and deduplicate all valid entries and move broken lines to the quarantine file
+/// Redundant end entries at the start or following another end entry are dropped
+fn fix_entries(lines: Vec<CheckedLine>) -> Result<()> {
+    let mut broken = Vec::new();
+    let mut entries = Vec::new();
+    for line in lines {
+        match line.entry {
+            Ok(entry) => entries.push(entry),
+            Err(_) => broken.push(line.raw),
+        }
+    }
+
+    entries.sort_by_key(|e| *e.time_stamp());
+    let mut seen = HashSet::new();
+    entries.retain(|e| seen.insert(e.to_string()));
+    let mut last_was_end = true;
+    entries.retain(|e| {
+        let is_end = matches!(e, ActivityEntry::End(_));
+        let redundant = is_end && last_was_end;
+        last_was_end = is_end;
+        !redundant
+    });
+
+    if !broken.is_empty() {
+        let quarantine_path = files::get_quarantine_file_path()?;
+        let mut file = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&quarantine_path)?;
+        for line in &broken {
+            writeln!(&mut file, "{line}")?;
+        }
+        println!(
+            "Moved {} broken lines to {:?}",
+            broken
+                .len()
+                .if_supports_color(Stream::Stdout, |n| n.yellow()),
+            quarantine_path
+        );
+    }
+    rewrite_history(&entries)?;
+    println!(
+        "{} {} entries",
+        "Repaired".if_supports_color(Stream::Stdout, |n| n.green()),
+        entries.len()
+    );
+    Ok(())
+}
@@ -0,0 +1,68 @@
+use std::rc::Rc;
+
+use chrono::{Days, Local};
+use color_eyre::eyre::{Result, format_err};
+
+use crate::{
+    activity_entry::activity_groupings::get_attendance_ranges,
+    activity_range::DateSpan,
+    cli,
+    compliance::{Violation, find_violations},
+    get_config, print_smart_table,
+};
+
+use super::get_activities_since;
+
+pub fn check_compliance(compliance_opts: &cli::CheckCompliance) -> Result<()> {
+    let config = get_config()?;
+    let now = Local::now();
+    let today = now.date_naive();
+    let (first, last) = compliance_opts
+        .days
+        .clone()
+        .unwrap_or(DateSpan::month_of(today))
+        .days(today)?;
+
+    // The day before is needed to check the rest before the first day
+    let (start, end) = DateSpan::Days(first - Days::new(1), last).bounds(&now)?;
+    let activities = get_activities_since(&start, end.as_ref())?;
+    let ranges = get_attendance_ranges(&activities);
+    let violations = find_violations(
+        &ranges,
+        &config.compliance,
+        &config.balance.compensation_types,
+        now,
+    )
+    .into_iter()
+    .filter(|v| v.date >= first && v.date <= last)
+    .collect::<Vec<_>>();
+
+    if violations.is_empty() {
+        println!("No violations found between {first} and {last}");
+        return Ok(());
+    }
+    print_violations(&violations);
+    Err(format_err!(
+        "found {} violations between {first} and {last}",
+        violations.len()
+    ))
+}
+
+fn print_violations(violations: &[Violation]) {
+    let mut col_date: Vec<Rc<str>> = Vec::new();
+    let mut col_day: Vec<Rc<str>> = Vec::new();
+    let mut col_rule: Vec<Rc<str>> = Vec::new();
+    let mut col_violation: Vec<Rc<str>> = Vec::new();
+    for violation in violations {
+        col_date.push(violation.date.format("%Y-%m-%d").to_string().into());
+        col_day.push(violation.date.format("%a").to_string().into());
+        col_rule.push(violation.rule.to_string().into());
+        col_violation.push(violation.message.as_str().into());
+    }
+    print_smart_table! {
+        "Date" => col_date,
+        "Day" => col_day,
+        "Rule" => col_rule,
+        "Violation" => col_violation,
+    }
+}
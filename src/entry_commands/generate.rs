@@ -1,37 +1,66 @@
 use std::{collections::HashMap, fs, io::Write, path::PathBuf, rc::Rc};
 
-use chrono::{Datelike, Local, NaiveDate};
-use color_eyre::eyre::{Result, format_err};
+use chrono::{DateTime, Datelike, Local, NaiveDate};
+use color_eyre::{
+    Section,
+    eyre::{Context, Result, format_err},
+};
 
 use crate::{
-    activity_entry::activity_groupings::{CollapsedActivity, collapse_activities},
-    activity_range::InLast,
+    activity_entry::{
+        TrackedActivity,
+        activity_groupings::{
+            AttendanceRange, CollapsedActivity, collapse_activities, get_attendance_ranges,
+        },
+    },
+    activity_range::{ActivityRange, DateSpan, InLast},
     cli,
-    config::Config,
+    config::{AttendanceRules, Config, OutputConfig, OutputProfiles, delta_to_hours},
+    format_string::VariableValue,
     get_config,
+    trackable::BUILTIN_ACTIVITY_IDLE_NAME,
 };
 
 use super::get_activities_since;
 
 pub fn handle_generate(generate_opts: &cli::Generate) -> Result<()> {
     let now = Local::now();
-    let start_time = InLast::Months(0).back_from(&now);
-    let activities = get_activities_since(&start_time)?;
-    let collapsed = collapse_activities(&activities, now);
+    let (start_time, end_time) = get_generated_timeframe(generate_opts, &now)?;
+    let activities = get_activities_since(&start_time, end_time.as_ref())?;
 
     let config = get_config()?;
-    let keys = config.output.keys.join(&config.output.delimiter);
-    let lines = collapsed
+    let profile = generate_opts.profile.as_deref();
+    let (output, rows) = if generate_opts.attendance {
+        let output = config
+            .attendance_output
+            .as_ref()
+            .ok_or(format_err!("no attendance output is configured"))
+            .with_note(|| "add an [attendance_output] section to your config file")?;
+        let output = get_output_profile(output, profile, "attendance_output")?;
+        let rows = get_attendance_ranges(&activities)
+            .iter()
+            .map(|r| vars_per_attendance_range(r, &config.attendance_rules, now))
+            .collect::<Vec<_>>();
+        (output, rows)
+    } else {
+        assert_wbs_format(&config, &activities)?;
+        let rows = collapse_activities(&activities, now)
+            .iter()
+            .map(vars_per_collapsed_activity)
+            .collect();
+        (get_output_profile(&config.output, profile, "output")?, rows)
+    };
+
+    let keys = output.keys.join(&output.delimiter);
+    let lines = rows
         .iter()
-        .map(|c| {
-            let vars = vars_per_collapsed_activity(c);
-            config
-                .output
+        .map(|vars| {
+            output
                 .values
                 .iter()
-                .map(|v| v.evaluate(&vars))
+                .map(|v| v.evaluate(vars))
                 .collect::<core::result::Result<Vec<_>, _>>()
-                .map(|s| s.join(&config.output.delimiter))
+                .map(|s| s.join(&output.delimiter))
         })
         .collect::<core::result::Result<Vec<_>, _>>()?
         .join("\r\n");
@@ -42,7 +71,7 @@ pub fn handle_generate(generate_opts: &cli::Generate) -> Result<()> {
     }
 
     let file_vars = vars_per_generated_file(&config, start_time.date_naive());
-    let default_name = config.output.file_name_format.evaluate(&file_vars)?;
+    let default_name = output.file_name_format.evaluate(&file_vars)?;
     let file_path = generate_opts.file_path.as_ref().unwrap_or(&default_name);
     let mut file_path = PathBuf::from(file_path);
     while fs::exists(&file_path)? {
@@ -65,6 +94,70 @@ pub fn handle_generate(generate_opts: &cli::Generate) -> Result<()> {
     Ok(())
 }
 
+/// Get the requested profile of the output config in the `section` of the config file
+fn get_output_profile<'a>(
+    output: &'a OutputProfiles,
+    profile: Option<&str>,
+    section: &str,
+) -> Result<&'a OutputConfig> {
+    output.get(profile).ok_or_else(|| {
+        let name = profile.unwrap_or_default();
+        let note = match output
+            .profiles
+            .keys()
+            .map(String::as_str)
+            .collect::<Vec<_>>()
+        {
+            names if names.is_empty() => {
+                format!("add an [{section}.{name}] section to your config file")
+            }
+            names => format!("available profiles are {}", names.join(", ")),
+        };
+        format_err!("there is no {section} profile '{name}'").with_note(|| note)
+    })
+}
+
+/// Make sure no activity with a malformed wbs ends up in the output
+/// The builtin idle activity is exempt, as it is never booked on
+fn assert_wbs_format(config: &Config, activities: &[TrackedActivity]) -> Result<()> {
+    let violations = activities
+        .iter()
+        .filter(|a| a.name() != BUILTIN_ACTIVITY_IDLE_NAME)
+        .filter_map(|a| {
+            let e = config.assert_format.check_wbs(a.wbs()).err()?;
+            let start = a.start_time().format("%Y-%m-%d %H:%M:%S");
+            Some(format!("entry at {start}: activity '{}': {e}", a.name()))
+        })
+        .collect::<Vec<_>>();
+    if violations.is_empty() {
+        return Ok(());
+    }
+    Err(format_err!("{}", violations.join("\n")))
+        .wrap_err("refusing to generate output with malformed wbs")
+        .with_note(|| "use `timetrack edit entries` to fix the affected entries")
+}
+
+/// Get the start and optional end of the time frame selected by `generate_opts`
+fn get_generated_timeframe(
+    generate_opts: &cli::Generate,
+    now: &DateTime<Local>,
+) -> Result<(DateTime<Local>, Option<DateTime<Local>>)> {
+    if let Some(month) = &generate_opts.month {
+        return month.bounds(now);
+    }
+    if generate_opts.previous {
+        return DateSpan::LastMonth.bounds(now);
+    }
+    match &generate_opts.last {
+        None => Ok((InLast::Months(0).back_from(now), None)),
+        Some(ActivityRange::Timeframe(tf)) => Ok((tf.back_from(now), None)),
+        Some(ActivityRange::Span(span)) => span.bounds(now),
+        Some(ActivityRange::Count(_)) => Err(format_err!(
+            "output can only be generated for a time frame, not a number of activities"
+        )),
+    }
+}
+
 fn vars_per_generated_file(cfg: &Config, date: NaiveDate) -> HashMap<&'static str, Rc<str>> {
     HashMap::from([
         // From config
@@ -74,27 +167,97 @@ fn vars_per_generated_file(cfg: &Config, date: NaiveDate) -> HashMap<&'static st
         ("performance_type", Rc::from(cfg.performance_type.as_str())),
         ("accounting_cycle", Rc::from(cfg.accounting_cycle.as_str())),
         // Regarding date
+        ("date", Rc::from(date.format("%Y-%m-%d").to_string())),
         ("year", Rc::from(date.year().to_string())),
         ("month", Rc::from(format!("{:02}", date.month()))),
         ("day", Rc::from(format!("{:02}", date.day()))),
     ])
 }
 
-fn vars_per_collapsed_activity(activity: &CollapsedActivity) -> HashMap<&'static str, Rc<str>> {
+fn vars_per_collapsed_activity(
+    activity: &CollapsedActivity,
+) -> HashMap<&'static str, VariableValue> {
     let date = activity.start_time().date_naive();
     let seconds = activity.duration().as_seconds_f64();
     HashMap::from([
         // Regarding date
-        ("year", Rc::from(date.year().to_string())),
-        ("month", Rc::from(format!("{:02}", date.month()))),
-        ("day", Rc::from(format!("{:02}", date.day()))),
+        ("date", date.format("%Y-%m-%d").to_string().into()),
+        ("year", date.year().to_string().into()),
+        ("month", format!("{:02}", date.month()).into()),
+        ("day", format!("{:02}", date.day()).into()),
         // Regarding duration
-        ("hours", Rc::from(format!("{:.2}", seconds / 3600.0))),
-        ("minutes", Rc::from(format!("{:.2}", seconds / 60.0))),
-        ("seconds", Rc::from(format!("{:.2}", seconds))),
+        ("hours", VariableValue::number(seconds / 3600.0)),
+        ("minutes", VariableValue::number(seconds / 60.0)),
+        ("seconds", VariableValue::number(seconds)),
         // Other
         ("attendance_type", activity.attendance().into()),
         ("description", activity.description().into()),
         ("wbs", activity.wbs().into()),
+        ("tags", activity.tags().join(",").into()),
     ])
 }
+
+fn vars_per_attendance_range(
+    range: &AttendanceRange,
+    rules: &AttendanceRules,
+    end_fallback: DateTime<Local>,
+) -> HashMap<&'static str, VariableValue> {
+    let adjusted = range.adjust(rules, end_fallback);
+    let date = adjusted.start.date_naive();
+    let end = adjusted.end.map(|t| t.format("%H:%M").to_string());
+    let hours = delta_to_hours(adjusted.duration);
+    let hours_adjusted = delta_to_hours(adjusted.adjusted_duration);
+    HashMap::from([
+        // Regarding date
+        ("date", date.format("%Y-%m-%d").to_string().into()),
+        ("year", date.year().to_string().into()),
+        ("month", format!("{:02}", date.month()).into()),
+        ("day", format!("{:02}", date.day()).into()),
+        // Regarding time
+        ("start", adjusted.start.format("%H:%M").to_string().into()),
+        ("end", end.unwrap_or_default().into()),
+        ("hours", VariableValue::number(hours_adjusted)),
+        ("unadjusted_hours", VariableValue::number(hours)),
+        // Other
+        ("attendance_type", range.attendance().into()),
+    ])
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::Parser;
+
+    use super::*;
+
+    fn generate_opts(args: &[&str]) -> Result<cli::Generate, clap::Error> {
+        cli::Generate::try_parse_from(std::iter::once("generate").chain(args.iter().copied()))
+    }
+
+    #[test]
+    fn month_selects_its_days() {
+        let now = Local::now();
+        let opts = generate_opts(&["--month", "2026-09"]).unwrap();
+        let (start, end) = get_generated_timeframe(&opts, &now).unwrap();
+        assert_eq!(
+            start.date_naive(),
+            NaiveDate::from_ymd_opt(2026, 9, 1).unwrap()
+        );
+        assert_eq!(
+            end.map(|e| e.date_naive()),
+            NaiveDate::from_ymd_opt(2026, 10, 1)
+        );
+    }
+
+    #[test]
+    fn out_of_range_month_is_an_error() {
+        for month in ["262142-12", "2026-13", "2026-4294967297"] {
+            let e = generate_opts(&["--month", month]).unwrap_err();
+            assert!(e.to_string().contains("expected a month"), "{e}");
+        }
+        let opts = cli::Generate {
+            month: Some(DateSpan::Days(NaiveDate::MAX, NaiveDate::MAX)),
+            ..generate_opts(&[]).unwrap()
+        };
+        assert!(get_generated_timeframe(&opts, &Local::now()).is_err());
+    }
+}
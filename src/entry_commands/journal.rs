@@ -0,0 +1,239 @@
+use std::{fs, str::FromStr};
+
+use chrono::{DateTime, Local};
+use color_eyre::{
+    Section,
+    eyre::{Result, format_err},
+};
+use owo_colors::{OwoColorize, Stream};
+use serde::{Deserialize, Serialize};
+
+use crate::{activity_entry::ActivityEntry, cli, files};
+
+use super::{get_all_entries, write_all_entries};
+
+/// Operations older than this are dropped from the journal
+const JOURNAL_LENGTH: usize = 100;
+
+/// A single change to the entry log
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Change {
+    Insert(#[serde(with = "entry_line")] ActivityEntry),
+    Remove(#[serde(with = "entry_line")] ActivityEntry),
+}
+impl Change {
+    fn inverse(&self) -> Change {
+        match self {
+            Change::Insert(entry) => Change::Remove(entry.clone()),
+            Change::Remove(entry) => Change::Insert(entry.clone()),
+        }
+    }
+}
+
+/// All changes made by a single command
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Operation {
+    command: String,
+    time: DateTime<Local>,
+    changes: Vec<Change>,
+    #[serde(default)]
+    undone: bool,
+}
+
+/// Record the `changes` made by `command`, discarding everything that could be redone
+/// The journal is written before `apply` makes the changes and restored if that fails
+/// The caller must hold the lock on the entry file
+pub fn record(
+    command: &str,
+    changes: Vec<Change>,
+    apply: impl FnOnce() -> Result<()>,
+) -> Result<()> {
+    let previous = read_journal()?;
+    let mut journal = previous.clone();
+    journal.retain(|op| !op.undone);
+    journal.push(Operation {
+        command: command.to_string(),
+        time: Local::now(),
+        changes,
+        undone: false,
+    });
+    let excess = journal.len().saturating_sub(JOURNAL_LENGTH);
+    write_journal_then(&previous, &journal[excess..], apply)
+}
+
+/// Forget all operations, for changes to the entry log that can't be undone
+/// The caller must hold the lock on the entry file
+pub fn discard() -> Result<()> {
+    let path = files::get_journal_file_path()?;
+    if fs::exists(&path)? {
+        fs::remove_file(&path)?;
+    }
+    Ok(())
+}
+
+pub fn undo(undo_opts: &cli::Undo) -> Result<()> {
+    let _lock = files::lock_entry_file()?;
+    let mut journal = read_journal()?;
+    let done = journal.iter().filter(|op| !op.undone).count();
+    if done == 0 {
+        return Err(format_err!("there is nothing to undo"));
+    }
+    let count = undo_opts.count.get().min(done);
+
+    let previous = journal.clone();
+    let mut entries = get_all_entries()?;
+    for op in journal[done - count..done].iter_mut().rev() {
+        let inverse: Vec<Change> = op.changes.iter().rev().map(Change::inverse).collect();
+        apply_changes(&mut entries, &inverse).map_err(|e| wrap_stale(e, op))?;
+        print_operation("Undid", op, &inverse);
+        op.undone = true;
+    }
+    write_journal_then(&previous, &journal, || write_all_entries(&entries))
+}
+
+pub fn redo(redo_opts: &cli::Redo) -> Result<()> {
+    let _lock = files::lock_entry_file()?;
+    let mut journal = read_journal()?;
+    let done = journal.iter().filter(|op| !op.undone).count();
+    if done == journal.len() {
+        return Err(format_err!("there is nothing to redo"));
+    }
+    let count = redo_opts.count.get().min(journal.len() - done);
+
+    let previous = journal.clone();
+    let mut entries = get_all_entries()?;
+    for op in journal[done..done + count].iter_mut() {
+        apply_changes(&mut entries, &op.changes).map_err(|e| wrap_stale(e, op))?;
+        print_operation("Redid", op, &op.changes);
+        op.undone = false;
+    }
+    write_journal_then(&previous, &journal, || write_all_entries(&entries))
+}
+
+/// Apply `changes` in order, keeping `entries` chronological
+/// Fails without writing anything if an entry to remove doesn't exist anymore
+fn apply_changes(entries: &mut Vec<ActivityEntry>, changes: &[Change]) -> Result<()> {
+    for change in changes {
+        match change {
+            Change::Insert(entry) => {
+                let position = entries.partition_point(|e| e.time_stamp() <= entry.time_stamp());
+                entries.insert(position, entry.clone());
+            }
+            Change::Remove(entry) => {
+                let line = entry.to_string();
+                let position = entries
+                    .iter()
+                    .rposition(|e| e.to_string() == line)
+                    .ok_or_else(|| format_err!("entry not found: {line}"))?;
+                entries.remove(position);
+            }
+        }
+    }
+    Ok(())
+}
+
+fn wrap_stale(error: color_eyre::Report, op: &Operation) -> color_eyre::Report {
+    let time = op.time.format("%Y-%m-%d %H:%M:%S");
+    error
+        .wrap_err(format!(
+            "the entry log was changed after '{}' at {time}",
+            op.command
+        ))
+        .note("no changes were made, use `timetrack edit` to revert it manually")
+}
+
+fn print_operation(verb: &str, op: &Operation, changes: &[Change]) {
+    println!(
+        "{verb} '{}' from {}",
+        op.command.if_supports_color(Stream::Stdout, |n| n.bold()),
+        op.time.format("%Y-%m-%d %H:%M:%S")
+    );
+    for change in changes {
+        match change {
+            Change::Insert(entry) => println!(
+                "  {} {}",
+                "+".if_supports_color(Stream::Stdout, |n| n.green()),
+                describe_entry(entry)
+            ),
+            Change::Remove(entry) => println!(
+                "  {} {}",
+                "-".if_supports_color(Stream::Stdout, |n| n.red()),
+                describe_entry(entry)
+            ),
+        }
+    }
+}
+
+fn describe_entry(entry: &ActivityEntry) -> String {
+    let time = entry.time_stamp().format("%Y-%m-%d %H:%M:%S");
+    match entry {
+        ActivityEntry::Start(start) => {
+            let description = match start.description() {
+                "" => String::new(),
+                d => format!(" ({d})"),
+            };
+            format!(
+                "start '{}' at {time} as {}{description}",
+                start.name(),
+                start.attendance()
+            )
+        }
+        ActivityEntry::End(_) => format!("end at {time}"),
+    }
+}
+
+fn read_journal() -> Result<Vec<Operation>> {
+    let path = files::get_journal_file_path()?;
+    if !fs::exists(&path)? {
+        return Ok(Vec::new());
+    }
+    fs::read_to_string(&path)?
+        .lines()
+        .map(|l| serde_json::from_str(l).map_err(Into::into))
+        .collect::<Result<Vec<_>>>()
+        .with_note(|| format!("remove {path:?} to start a new journal"))
+}
+
+/// Write `journal` before `apply` changes the entries, restoring the `previous` journal on failure
+fn write_journal_then(
+    previous: &[Operation],
+    journal: &[Operation],
+    apply: impl FnOnce() -> Result<()>,
+) -> Result<()> {
+    write_journal(journal)?;
+    let Err(error) = apply() else {
+        return Ok(());
+    };
+    match write_journal(previous) {
+        Ok(()) => Err(error),
+        Err(_) => Err(error).with_note(
+            || "the journal could not be restored, remove it to use undo and redo again",
+        ),
+    }
+}
+
+fn write_journal(journal: &[Operation]) -> Result<()> {
+    let path = files::get_journal_file_path()?;
+    let mut content = String::new();
+    for op in journal {
+        content.push_str(&serde_json::to_string(op)?);
+        content.push('\n');
+    }
+    files::write_atomically(&path, &content)
+}
+
+/// Journal entries are stored in the same format as lines of the entry file
+mod entry_line {
+    use super::*;
+    use serde::{Deserializer, Serializer, de::Error};
+
+    pub fn serialize<S: Serializer>(entry: &ActivityEntry, s: S) -> Result<S::Ok, S::Error> {
+        s.collect_str(entry)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<ActivityEntry, D::Error> {
+        let line = String::deserialize(d)?;
+        ActivityEntry::from_str(&line).map_err(D::Error::custom)
+    }
+}
@@ -1,23 +1,33 @@
-use std::rc::Rc;
+use std::{collections::BTreeMap, rc::Rc};
 
-use chrono::{DurationRound, Local, TimeDelta};
+use chrono::{DateTime, Local, NaiveDate, TimeDelta};
 use color_eyre::eyre::Result;
 use owo_colors::{OwoColorize, Stream};
+use serde::Serialize;
 
 use crate::{
     NONE_PRINT_VALUE,
     activity_entry::{
-        ActivityEntry, TrackedActivity,
+        ActivityEntry, ActivityStart, TrackedActivity,
         activity_groupings::{
-            AttendanceRange, CollapsedActivity, collapse_activities, get_attendance_ranges,
+            AdjustedAttendance, AttendanceRange, Break, CollapsedActivity, collapse_activities,
+            get_attendance_ranges, get_breaks,
         },
     },
     activity_range::ActivityRange,
-    cli, get_config, print_smart_list, print_smart_table,
+    calendar::{Calendar, DayBalance, DayKind},
+    cli,
+    compliance::find_violations,
+    config::{AttendanceRules, delta_to_hours},
+    get_config,
+    machine_readable::{Record, print_records},
+    print_smart_list, print_smart_table,
 };
 
 use super::{get_activities_since, get_last_entry, get_last_n_activities};
 
+const BREAK_PRINT_VALUE: &str = "(break)";
+
 pub fn show_activities(show_opts: &cli::Show) -> Result<()> {
     match &show_opts.last {
         ActivityRange::Count(0) => show_current_entry(show_opts),
@@ -27,9 +37,18 @@ pub fn show_activities(show_opts: &cli::Show) -> Result<()> {
 
 fn show_current_entry(show_opts: &cli::Show) -> Result<()> {
     let entry = get_last_entry()?;
+    if let Some(format) = &show_opts.format {
+        return print_records(entry, format);
+    }
     match entry {
         None => println!("You have not recorded any data yet"),
         Some(entry) if show_opts.machine_readable => println!("{entry}"),
+        Some(entry @ ActivityEntry::End(_)) if entry.is_pause() => {
+            println!(
+                "You are taking a break since {}, use `timetrack resume` to continue",
+                entry.time_stamp().format("%H:%M")
+            )
+        }
         Some(ActivityEntry::End(_)) => {
             println!("You are not tracking any activity")
         }
@@ -60,14 +79,28 @@ fn show_current_entry(show_opts: &cli::Show) -> Result<()> {
 }
 
 fn show_activity_range(show_opts: &cli::Show, quantity: &ActivityRange) -> Result<()> {
-    let activities = match quantity {
-        ActivityRange::Count(n) => get_last_n_activities(*n as usize)?,
-        ActivityRange::Timeframe(tf) => get_activities_since(&tf.back_from(&Local::now()))?,
+    let filter = |a: &ActivityStart| matches_filter(&show_opts.filter, a);
+    let mut activities = match quantity {
+        ActivityRange::Count(n) => get_last_n_activities(*n as usize, filter)?,
+        ActivityRange::Timeframe(tf) => get_activities_since(&tf.back_from(&Local::now()), None)?,
+        ActivityRange::Span(span) => {
+            let (start, end) = span.bounds(&Local::now())?;
+            get_activities_since(&start, end.as_ref())?
+        }
     };
 
+    let unfiltered_count = activities.len();
+    let breaks = get_breaks(&activities, Local::now());
+    activities.retain(|a| filter(&a.start_entry));
+    if let Some(format) = &show_opts.format {
+        return print_activity_records(&activities, &show_opts.mode, quantity, format);
+    }
+
     if activities.is_empty() {
         if get_last_entry()?.is_none() {
             println!("You have not recorded any data yet")
+        } else if unfiltered_count > 0 {
+            println!("None of the activities in the requested timeframe match your filters");
         } else {
             println!("You have not recorded any data in the requested timeframe");
         }
@@ -76,37 +109,101 @@ fn show_activity_range(show_opts: &cli::Show, quantity: &ActivityRange) -> Resul
 
     match show_opts.mode {
         cli::ShowMode::Entries => {
-            show_individual_activities(&activities, show_opts.machine_readable);
+            show_individual_activities(&activities, &breaks, show_opts.machine_readable);
         }
         cli::ShowMode::Collapsed => {
-            show_collapsed_activities(&activities, show_opts.machine_readable);
+            show_collapsed_activities(&activities, &breaks, show_opts.machine_readable);
         }
         cli::ShowMode::Attendance => {
-            show_daily_attendance(&activities, show_opts.machine_readable);
+            let days = get_attendance_days(quantity)?;
+            show_daily_attendance(&activities, days, show_opts.machine_readable)?;
         }
         cli::ShowMode::Time => {
             show_activity_time(&activities, show_opts.machine_readable);
         }
+        cli::ShowMode::Tags => {
+            show_tag_time(&activities, show_opts.machine_readable);
+        }
     }
 
     Ok(())
 }
 
+fn matches_filter(filter: &cli::ShowFilter, activity: &ActivityStart) -> bool {
+    let matches_activity = filter.activity.as_deref().is_none_or(|path| {
+        let path = path.trim_end_matches('/');
+        match activity.name().strip_prefix(path) {
+            Some(rest) => rest.is_empty() || rest.starts_with('/'),
+            None => false,
+        }
+    });
+    matches_activity
+        && filter.wbs.as_deref().is_none_or(|w| w == activity.wbs())
+        && filter
+            .attendance
+            .as_deref()
+            .is_none_or(|a| a == activity.attendance())
+        && filter
+            .grep
+            .as_ref()
+            .is_none_or(|r| r.is_match(activity.description()))
+        && filter
+            .tags
+            .iter()
+            .all(|t| activity.tags().iter().any(|a| a.as_ref() == t))
+}
+
+fn print_activity_records(
+    activities: &[TrackedActivity],
+    mode: &cli::ShowMode,
+    quantity: &ActivityRange,
+    format: &cli::OutputFormat,
+) -> Result<()> {
+    match mode {
+        cli::ShowMode::Entries => print_records(activities, format),
+        cli::ShowMode::Collapsed => {
+            print_records(collapse_activities(activities, Local::now()), format)
+        }
+        cli::ShowMode::Attendance => {
+            let records = get_attendance_records(activities, get_attendance_days(quantity)?)?;
+            print_records(records, format)
+        }
+        cli::ShowMode::Time => {
+            let seconds = get_total_time(activities).as_seconds_f64();
+            print_records([TimeRecord { seconds }], format)
+        }
+        cli::ShowMode::Tags => {
+            let records = get_time_per_tag(activities)
+                .into_iter()
+                .map(|(tag, time)| TagRecord {
+                    tag,
+                    seconds: time.as_seconds_f64(),
+                });
+            print_records(records, format)
+        }
+    }
+}
+
 // ------- //
 // Entries //
 // ------- //
 
-fn show_individual_activities(activities: &[TrackedActivity], machine_readable: bool) {
+fn show_individual_activities(
+    activities: &[TrackedActivity],
+    breaks: &[Break],
+    machine_readable: bool,
+) {
     if machine_readable {
         for activity in activities {
             println!("{activity}");
         }
     } else {
-        print_activitiy_table(activities);
+        print_activitiy_table(activities, breaks);
     }
 }
 
-fn print_activitiy_table(activities: &[TrackedActivity]) {
+/// Breaks are shown right after the activity that was paused
+fn print_activitiy_table(activities: &[TrackedActivity], breaks: &[Break]) {
     let mut col_date: Vec<Rc<str>> = Vec::new();
     let mut col_start: Vec<Rc<str>> = Vec::new();
     let mut col_end: Vec<Rc<str>> = Vec::new();
@@ -115,6 +212,7 @@ fn print_activitiy_table(activities: &[TrackedActivity]) {
     let mut col_attendance: Vec<Rc<str>> = Vec::new();
     let mut col_wbs: Vec<Rc<str>> = Vec::new();
     let mut col_description: Vec<Rc<str>> = Vec::new();
+    let mut col_tags: Vec<Rc<str>> = Vec::new();
     let none_value: Rc<str> = Rc::from(NONE_PRINT_VALUE);
 
     for activity in activities {
@@ -136,6 +234,35 @@ fn print_activitiy_table(activities: &[TrackedActivity]) {
             "" => none_value.clone(),
             s => s.into(),
         });
+        col_tags.push(match activity.tags() {
+            [] => none_value.clone(),
+            tags => tags
+                .iter()
+                .map(|t| format!("+{t}"))
+                .collect::<Vec<_>>()
+                .join(" ")
+                .into(),
+        });
+
+        let Some(gap) = breaks
+            .iter()
+            .find(|b| Some(&b.start) == activity.end_time())
+        else {
+            continue;
+        };
+        let hours = gap.duration(Local::now()).as_seconds_f64() / 3600.0;
+        col_date.push(gap.start.format("%Y-%m-%d").to_string().into());
+        col_start.push(gap.start.format("%H:%M:%S").to_string().into());
+        col_end.push(match gap.end {
+            Some(t) => t.format("%H:%M:%S").to_string().into(),
+            None => none_value.clone(),
+        });
+        col_hours.push(format!("{hours:.2}").into());
+        col_name.push(BREAK_PRINT_VALUE.into());
+        col_attendance.push(none_value.clone());
+        col_wbs.push(none_value.clone());
+        col_description.push(none_value.clone());
+        col_tags.push(none_value.clone());
     }
 
     print_smart_table! {
@@ -147,6 +274,7 @@ fn print_activitiy_table(activities: &[TrackedActivity]) {
         "Attendance" => col_attendance,
         "WBS" => col_wbs,
         "Description" => col_description,
+        "Tags" => col_tags,
     }
 }
 
@@ -154,18 +282,23 @@ fn print_activitiy_table(activities: &[TrackedActivity]) {
 // Collapsed //
 // --------- //
 
-fn show_collapsed_activities(activities: &[TrackedActivity], machine_readable: bool) {
+fn show_collapsed_activities(
+    activities: &[TrackedActivity],
+    breaks: &[Break],
+    machine_readable: bool,
+) {
     let collapsed_activities = collapse_activities(activities, Local::now());
     if machine_readable {
         for collapsed in collapsed_activities {
             println!("{collapsed}");
         }
     } else {
-        print_collapsed_activity_table(&collapsed_activities)
+        print_collapsed_activity_table(&collapsed_activities, breaks)
     }
 }
 
-fn print_collapsed_activity_table(collapsed_activities: &[CollapsedActivity]) {
+/// All breaks of a day are summed up after the last activity of that day
+fn print_collapsed_activity_table(collapsed_activities: &[CollapsedActivity], breaks: &[Break]) {
     let mut col_date: Vec<Rc<str>> = Vec::new();
     let mut col_hours: Vec<Rc<str>> = Vec::new();
     let mut col_attendance: Vec<Rc<str>> = Vec::new();
@@ -173,7 +306,7 @@ fn print_collapsed_activity_table(collapsed_activities: &[CollapsedActivity]) {
     let mut col_description: Vec<Rc<str>> = Vec::new();
     let none_value: Rc<str> = Rc::from(NONE_PRINT_VALUE);
 
-    for collapsed in collapsed_activities {
+    for (i, collapsed) in collapsed_activities.iter().enumerate() {
         let start = collapsed.start_time();
         let hours = collapsed.duration().as_seconds_f64() / 3600.0;
         col_date.push(start.format("%Y-%m-%d").to_string().into());
@@ -184,6 +317,24 @@ fn print_collapsed_activity_table(collapsed_activities: &[CollapsedActivity]) {
             "" => none_value.clone(),
             s => s.into(),
         });
+
+        let date = start.date_naive();
+        let last_of_day = collapsed_activities
+            .get(i + 1)
+            .is_none_or(|next| next.start_time().date_naive() != date);
+        let break_time: TimeDelta = breaks
+            .iter()
+            .filter(|b| b.start.date_naive() == date)
+            .map(|b| b.duration(Local::now()))
+            .sum();
+        if last_of_day && break_time > TimeDelta::zero() {
+            let hours = break_time.as_seconds_f64() / 3600.0;
+            col_date.push(start.format("%Y-%m-%d").to_string().into());
+            col_hours.push(format!("{hours:.2}").into());
+            col_attendance.push(none_value.clone());
+            col_wbs.push(none_value.clone());
+            col_description.push(BREAK_PRINT_VALUE.into());
+        }
     }
 
     print_smart_table! {
@@ -199,61 +350,271 @@ fn print_collapsed_activity_table(collapsed_activities: &[CollapsedActivity]) {
 // Attendance //
 // ---------- //
 
-fn show_daily_attendance(activities: &[TrackedActivity], machine_readable: bool) {
-    let ranges = get_attendance_ranges(activities);
+/// Get the days whose attendance is shown even without ranges, up to today
+fn get_attendance_days(quantity: &ActivityRange) -> Result<Option<(NaiveDate, NaiveDate)>> {
+    let today = Local::now().date_naive();
+    let days = match quantity {
+        ActivityRange::Count(_) => None,
+        ActivityRange::Timeframe(tf) => Some((tf.back_from(&Local::now()).date_naive(), today)),
+        ActivityRange::Span(span) => Some(span.days(today)?),
+    };
+    Ok(days.map(|(first, last)| (first, last.min(today))))
+}
+
+fn show_daily_attendance(
+    activities: &[TrackedActivity],
+    days: Option<(NaiveDate, NaiveDate)>,
+    machine_readable: bool,
+) -> Result<()> {
     if machine_readable {
-        for range in ranges {
-            println!("{range}");
+        print_attendance_lines(&get_attendance_records(activities, days)?);
+        return Ok(());
+    }
+    let config = get_config()?;
+    let calendar = Calendar::load(&config.calendar)?;
+    let ranges = get_attendance_ranges(activities);
+    let adjusted = adjust_ranges(&ranges, &config.attendance_rules);
+    let balances = get_day_balances(
+        &adjusted,
+        days,
+        &config.balance.compensation_types,
+        &calendar,
+    );
+    print_attendance_table(&get_attendance_rows(&adjusted, &balances));
+    if config.compliance.warn_in_show {
+        let violations = find_violations(
+            &ranges,
+            &config.compliance,
+            &config.balance.compensation_types,
+            Local::now(),
+        );
+        for violation in violations {
+            println!(
+                "{} {}: {}",
+                "warning:".if_supports_color(Stream::Stdout, |n| n.yellow()),
+                violation.date,
+                violation.message
+            );
         }
-    } else {
-        print_attendance_table(&ranges);
     }
+    Ok(())
+}
+
+/// A row of the daily attendance, days without attendance have a single row without a range
+struct AttendanceRow<'a> {
+    balance: &'a DayBalance,
+    range: Option<&'a (&'a AttendanceRange, AdjustedAttendance)>,
+    /// The target, actual and delta hours of a day are only shown in its last row
+    last_of_day: bool,
+}
+
+/// A row of the daily attendance in machine readable output
+/// The fields of the plain attendance ranges come first, followed by the hours of the text view
+#[derive(Serialize)]
+struct AttendanceRecord {
+    start: Option<DateTime<Local>>,
+    end: Option<DateTime<Local>>,
+    attendance: Option<Rc<str>>,
+    date: NaiveDate,
+    day: String,
+    hours: Option<f64>,
+    adjusted_hours: Option<f64>,
+    /// The target, actual and delta hours of a day are only set in its last record
+    target: Option<f64>,
+    actual: Option<f64>,
+    delta: Option<f64>,
+}
+impl Record for AttendanceRecord {
+    const FIELDS: &'static [&'static str] = &[
+        "start",
+        "end",
+        "attendance",
+        "date",
+        "day",
+        "hours",
+        "adjusted_hours",
+        "target",
+        "actual",
+        "delta",
+    ];
+}
+impl From<&AttendanceRow<'_>> for AttendanceRecord {
+    fn from(row: &AttendanceRow) -> Self {
+        let day = &row.balance.day;
+        let last_of_day = |hours: TimeDelta| row.last_of_day.then(|| delta_to_hours(hours));
+        AttendanceRecord {
+            start: row.range.map(|(r, _)| r.start()),
+            end: row.range.and_then(|(r, _)| r.end()),
+            attendance: row.range.map(|(r, _)| Rc::from(r.attendance())),
+            date: day.date,
+            day: day.describe(),
+            hours: row.range.map(|(_, a)| delta_to_hours(a.duration)),
+            adjusted_hours: row.range.map(|(_, a)| delta_to_hours(a.adjusted_duration)),
+            target: last_of_day(day.target),
+            actual: last_of_day(row.balance.worked),
+            delta: last_of_day(row.balance.delta()),
+        }
+    }
+}
+
+/// Get the same rows as the text view of the daily attendance
+fn get_attendance_records(
+    activities: &[TrackedActivity],
+    days: Option<(NaiveDate, NaiveDate)>,
+) -> Result<Vec<AttendanceRecord>> {
+    let config = get_config()?;
+    let calendar = Calendar::load(&config.calendar)?;
+    let ranges = get_attendance_ranges(activities);
+    let adjusted = adjust_ranges(&ranges, &config.attendance_rules);
+    let balances = get_day_balances(
+        &adjusted,
+        days,
+        &config.balance.compensation_types,
+        &calendar,
+    );
+    let rows = get_attendance_rows(&adjusted, &balances);
+    Ok(rows.iter().map(AttendanceRecord::from).collect())
+}
+
+fn adjust_ranges<'a>(
+    ranges: &'a [AttendanceRange],
+    rules: &AttendanceRules,
+) -> Vec<(&'a AttendanceRange, AdjustedAttendance)> {
+    ranges
+        .iter()
+        .map(|r| (r, r.adjust(rules, Local::now())))
+        .collect()
+}
+
+/// Compare the adjusted attendance to the target hours of all days from the first to the last range
+/// Days within `days` are included even without ranges
+/// Attendance of the `compensation_types` doesn't count towards the actual hours
+fn get_day_balances(
+    adjusted: &[(&AttendanceRange, AdjustedAttendance)],
+    days: Option<(NaiveDate, NaiveDate)>,
+    compensation_types: &[String],
+    calendar: &Calendar,
+) -> Vec<DayBalance> {
+    let range_dates = adjusted.iter().map(|(_, a)| a.start.date_naive());
+    let first = range_dates.clone().chain(days.map(|d| d.0)).min();
+    let last = range_dates.chain(days.map(|d| d.1)).max();
+    let (Some(first), Some(last)) = (first, last) else {
+        return Vec::new();
+    };
+    let attendance = adjusted
+        .iter()
+        .map(|(r, a)| (a.start.date_naive(), r.attendance(), a.adjusted_duration));
+    calendar.balance(first, last, attendance, compensation_types)
+}
+
+/// Group ranges by day, skipping regular days without ranges or target hours
+fn get_attendance_rows<'a>(
+    adjusted: &'a [(&'a AttendanceRange, AdjustedAttendance)],
+    balances: &'a [DayBalance],
+) -> Vec<AttendanceRow<'a>> {
+    let mut rows = Vec::new();
+    for balance in balances {
+        let day = &balance.day;
+        let day_ranges = adjusted
+            .iter()
+            .filter(|(_, a)| a.start.date_naive() == day.date)
+            .collect::<Vec<_>>();
+        if day_ranges.is_empty() && day.target.is_zero() && day.kind == DayKind::Regular {
+            continue;
+        }
+        let row_count = day_ranges.len().max(1);
+        rows.extend((0..row_count).map(|row| AttendanceRow {
+            balance,
+            range: day_ranges.get(row).copied(),
+            last_of_day: row + 1 == row_count,
+        }));
+    }
+    rows
 }
 
-fn print_attendance_table(ranges: &[AttendanceRange]) {
+/// Print one tab separated line per record, with empty fields where the table shows none
+fn print_attendance_lines(records: &[AttendanceRecord]) {
+    let time = |t: Option<DateTime<Local>>| t.map(|t| t.to_string()).unwrap_or_default();
+    let hours = |h: Option<f64>| h.map(|h| format!("{h:.2}")).unwrap_or_default();
+    for record in records {
+        println!(
+            "{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}",
+            time(record.start),
+            time(record.end),
+            record.attendance.as_deref().unwrap_or_default(),
+            record.date,
+            record.day,
+            hours(record.hours),
+            hours(record.adjusted_hours),
+            hours(record.target),
+            hours(record.actual),
+            hours(record.delta),
+        );
+    }
+}
+
+/// Print attendance ranges grouped by day, with the target, actual and delta hours of each day
+fn print_attendance_table(rows: &[AttendanceRow]) {
     let mut col_date: Vec<Rc<str>> = Vec::new();
+    let mut col_day: Vec<Rc<str>> = Vec::new();
     let mut col_start: Vec<Rc<str>> = Vec::new();
     let mut col_end: Vec<Rc<str>> = Vec::new();
     let mut col_hours: Vec<Rc<str>> = Vec::new();
     let mut col_hours_adjusted: Vec<Rc<str>> = Vec::new();
     let mut col_attendance: Vec<Rc<str>> = Vec::new();
+    let mut col_target: Vec<Rc<str>> = Vec::new();
+    let mut col_actual: Vec<Rc<str>> = Vec::new();
+    let mut col_delta: Vec<Rc<str>> = Vec::new();
     let none_value: Rc<str> = NONE_PRINT_VALUE.into();
-
-    for range in ranges {
-        let quantum = TimeDelta::minutes(15);
-        let start = range.start_time().duration_trunc(quantum).unwrap();
-        let end_value = range.end_time().copied().unwrap_or(Local::now());
-        let end = end_value.duration_round_up(quantum).unwrap();
-        let end_str = range
-            .end_time()
-            .map(|t| t.duration_round_up(quantum).unwrap())
-            .map(|t| t.format("%H:%M").to_string().into())
-            .unwrap_or(none_value.clone());
-
-        let delta = end - start;
-        let delta_adjusted = match delta {
-            d if d <= TimeDelta::hours(6) => d,
-            d => d - TimeDelta::minutes(30),
-        };
-
-        let hours = delta.as_seconds_f64() / 3600.0;
-        let hours_adjusted = delta_adjusted.as_seconds_f64() / 3600.0;
-
-        col_date.push(start.format("%Y-%m-%d").to_string().into());
-        col_start.push(start.format("%H:%M").to_string().into());
-        col_end.push(end_str);
-        col_hours.push(format!("{hours:.2}").into());
-        col_hours_adjusted.push(format!("{hours_adjusted:.2}").into());
-        col_attendance.push(range.attendance().into());
+    let empty_value: Rc<str> = "".into();
+
+    for row in rows {
+        let day = &row.balance.day;
+        col_date.push(day.date.format("%Y-%m-%d").to_string().into());
+        col_day.push(day.describe().into());
+        match row.range {
+            Some((range, adjusted)) => {
+                let end_str = adjusted
+                    .end
+                    .map(|t| t.format("%H:%M").to_string().into())
+                    .unwrap_or(none_value.clone());
+                col_start.push(adjusted.start.format("%H:%M").to_string().into());
+                col_end.push(end_str);
+                col_hours.push(format!("{:.2}", delta_to_hours(adjusted.duration)).into());
+                col_hours_adjusted
+                    .push(format!("{:.2}", delta_to_hours(adjusted.adjusted_duration)).into());
+                col_attendance.push(range.attendance().into());
+            }
+            None => {
+                col_start.push(none_value.clone());
+                col_end.push(none_value.clone());
+                col_hours.push(none_value.clone());
+                col_hours_adjusted.push(none_value.clone());
+                col_attendance.push(none_value.clone());
+            }
+        }
+        if !row.last_of_day {
+            col_target.push(empty_value.clone());
+            col_actual.push(empty_value.clone());
+            col_delta.push(empty_value.clone());
+            continue;
+        }
+        col_target.push(format!("{:.2}", delta_to_hours(day.target)).into());
+        col_actual.push(format!("{:.2}", delta_to_hours(row.balance.worked)).into());
+        col_delta.push(format!("{:+.2}", delta_to_hours(row.balance.delta())).into());
     }
 
     print_smart_table! {
         "Date" => col_date,
+        "Day" => col_day,
         "Start" => col_start,
         "End" => col_end,
         "Hours" => col_hours,
         "Adjusted Hours" => col_hours_adjusted,
         "Attendance" => col_attendance,
+        "Target" => col_target,
+        "Actual" => col_actual,
+        "Delta" => col_delta,
     }
 }
 
@@ -261,11 +622,16 @@ fn print_attendance_table(ranges: &[AttendanceRange]) {
 // Time //
 // ---- //
 
+#[derive(Serialize)]
+struct TimeRecord {
+    seconds: f64,
+}
+impl Record for TimeRecord {
+    const FIELDS: &'static [&'static str] = &["seconds"];
+}
+
 fn show_activity_time(activities: &[TrackedActivity], machine_readable: bool) {
-    let sum: TimeDelta = activities
-        .iter()
-        .map(|a| a.end_time().copied().unwrap_or(Local::now()) - a.start_time())
-        .sum();
+    let sum = get_total_time(activities);
     if machine_readable {
         println!("{:.2}", sum.as_seconds_f64());
     } else {
@@ -275,10 +641,77 @@ fn show_activity_time(activities: &[TrackedActivity], machine_readable: bool) {
     }
 }
 
+// ---- //
+// Tags //
+// ---- //
+
+#[derive(Serialize)]
+struct TagRecord {
+    tag: Option<Rc<str>>,
+    seconds: f64,
+}
+impl Record for TagRecord {
+    const FIELDS: &'static [&'static str] = &["tag", "seconds"];
+}
+
+fn show_tag_time(activities: &[TrackedActivity], machine_readable: bool) {
+    let totals = get_time_per_tag(activities);
+    if machine_readable {
+        for (tag, time) in totals {
+            println!("{}\t{:.2}", tag.unwrap_or_default(), time.as_seconds_f64());
+        }
+        return;
+    }
+
+    let mut col_tag: Vec<Rc<str>> = Vec::new();
+    let mut col_hours: Vec<Rc<str>> = Vec::new();
+    for (tag, time) in totals {
+        let hours = time.as_seconds_f64() / 3600.0;
+        col_tag.push(match tag {
+            Some(tag) => format!("+{tag}").into(),
+            None => NONE_PRINT_VALUE.into(),
+        });
+        col_hours.push(format!("{hours:.2}").into());
+    }
+    print_smart_table! {
+        "Tag" => col_tag,
+        "Hours" => col_hours,
+    }
+}
+
+/// Sum up the tracked time per tag, sorted by tag
+/// Activities count towards each of their tags, untagged activities are summed up last
+fn get_time_per_tag(activities: &[TrackedActivity]) -> Vec<(Option<Rc<str>>, TimeDelta)> {
+    let mut tagged: BTreeMap<Rc<str>, TimeDelta> = BTreeMap::new();
+    let mut untagged = TimeDelta::zero();
+    for activity in activities {
+        let time = activity.end_time().copied().unwrap_or(Local::now()) - activity.start_time();
+        if activity.tags().is_empty() {
+            untagged += time;
+        }
+        for tag in activity.tags() {
+            *tagged.entry(tag.clone()).or_default() += time;
+        }
+    }
+    let untagged = (untagged > TimeDelta::zero()).then_some((None, untagged));
+    tagged
+        .into_iter()
+        .map(|(tag, time)| (Some(tag), time))
+        .chain(untagged)
+        .collect()
+}
+
 // ------- //
 // General //
 // ------- //
 
+fn get_total_time(activities: &[TrackedActivity]) -> TimeDelta {
+    activities
+        .iter()
+        .map(|a| a.end_time().copied().unwrap_or(Local::now()) - a.start_time())
+        .sum()
+}
+
 fn format_time_delta(delta: &TimeDelta) -> String {
     let mut out = String::new();
     let days = delta.num_days();
@@ -304,3 +737,44 @@ fn format_time_delta(delta: &TimeDelta) -> String {
 
     out
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::machine_readable::write_records;
+
+    #[test]
+    fn attendance_header_keeps_its_layout() {
+        let day_off = AttendanceRecord {
+            start: None,
+            end: None,
+            attendance: None,
+            date: NaiveDate::from_ymd_opt(2026, 10, 2).unwrap(),
+            day: String::from("Fri"),
+            hours: None,
+            adjusted_hours: None,
+            target: Some(7.7),
+            actual: Some(0.0),
+            delta: Some(-7.7),
+        };
+        let mut out = Vec::new();
+        write_records(&mut out, [day_off], &cli::OutputFormat::Csv).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "schema_version,start,end,attendance,date,day,hours,adjusted_hours,target,actual,delta\n\
+             2,,,,2026-10-02,Fri,,,7.7,0.0,-7.7\n"
+        );
+
+        let mut out = Vec::new();
+        write_records(
+            &mut out,
+            Vec::<AttendanceRecord>::new(),
+            &cli::OutputFormat::Tsv,
+        )
+        .unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "schema_version\tstart\tend\tattendance\tdate\tday\thours\tadjusted_hours\ttarget\tactual\tdelta\n"
+        );
+    }
+}
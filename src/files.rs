@@ -1,4 +1,8 @@
-use std::{env, path::PathBuf};
+use std::{
+    env, fs,
+    io::Write,
+    path::{Path, PathBuf},
+};
 
 use color_eyre::eyre::Result;
 
@@ -6,6 +10,11 @@ const FS_SCOPE_NAME: &str = "timetrack";
 const DEFAULT_CONFIG_FILENAME: &str = "config.toml";
 const ACTIVITY_FILE_NAME: &str = "activities";
 const ENTRY_FILE_NAME: &str = "entries";
+const QUARANTINE_FILE_NAME: &str = "entries.quarantine";
+const JOURNAL_FILE_NAME: &str = "entries.journal";
+const DAYS_OFF_FILE_NAME: &str = "days_off";
+#[cfg(feature = "sqlite")]
+const DATABASE_FILE_NAME: &str = "timetrack.sqlite";
 const CONFIG_HOME_VAR: &str = "TIMETRACK_HOME";
 const DATA_HOME_VAR: &str = "TIMETRACK_DATA_HOME";
 
@@ -15,6 +24,31 @@ pub fn get_entry_file_path() -> Result<PathBuf> {
     Ok(path)
 }
 
+pub fn get_quarantine_file_path() -> Result<PathBuf> {
+    let mut path = get_data_home()?;
+    path.push(QUARANTINE_FILE_NAME);
+    Ok(path)
+}
+
+pub fn get_journal_file_path() -> Result<PathBuf> {
+    let mut path = get_data_home()?;
+    path.push(JOURNAL_FILE_NAME);
+    Ok(path)
+}
+
+pub fn get_days_off_file_path() -> Result<PathBuf> {
+    let mut path = get_data_home()?;
+    path.push(DAYS_OFF_FILE_NAME);
+    Ok(path)
+}
+
+#[cfg(feature = "sqlite")]
+pub fn get_database_path() -> Result<PathBuf> {
+    let mut path = get_data_home()?;
+    path.push(DATABASE_FILE_NAME);
+    Ok(path)
+}
+
 pub fn get_activity_file_path() -> Result<PathBuf> {
     let mut path = get_config_home()?;
     path.push(ACTIVITY_FILE_NAME);
@@ -27,6 +61,85 @@ pub fn get_main_config_path() -> Result<PathBuf> {
     Ok(path)
 }
 
+/// Resolve paths given in the config relative to the config directory
+pub fn resolve_config_path(path: &Path) -> Result<PathBuf> {
+    Ok(get_config_home()?.join(path))
+}
+
+/// Exclusive lock on a data file, released when dropped
+///
+/// All writers hold this lock for the whole read-modify-write cycle,
+/// so concurrent invocations can neither interleave writes nor act on stale data.
+/// The lock is taken on a separate file, as rewrites replace the data file itself.
+pub struct FileLock {
+    _file: fs::File,
+}
+
+/// Lock the entry file until the returned guard is dropped
+pub fn lock_entry_file() -> Result<FileLock> {
+    lock_file(&get_entry_file_path()?)
+}
+
+/// Lock the activity file until the returned guard is dropped
+/// When both are needed, lock the activity file before the entry file
+pub fn lock_activity_file() -> Result<FileLock> {
+    lock_file(&get_activity_file_path()?)
+}
+
+/// Lock the days off file until the returned guard is dropped
+pub fn lock_days_off_file() -> Result<FileLock> {
+    lock_file(&get_days_off_file_path()?)
+}
+
+fn lock_file(path: &Path) -> Result<FileLock> {
+    let file_name = path
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or_default();
+    let lock_path = path.with_file_name(format!(".{file_name}.lock"));
+    if let Some(p) = path.parent() {
+        fs::create_dir_all(p)?;
+    }
+    let file = fs::OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(lock_path)?;
+    file.lock()?;
+    Ok(FileLock { _file: file })
+}
+
+/// Append a single line to `path` with a single write
+pub fn append_line(path: &Path, line: &str) -> Result<()> {
+    if let Some(p) = path.parent() {
+        fs::create_dir_all(p)?;
+    }
+    let mut file = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)?;
+    file.write_all(format!("{line}\n").as_bytes())?;
+    Ok(())
+}
+
+/// Replace the contents of `path` without ever leaving it partially written
+/// The content is written to a temporary file first, which is then renamed to `path`
+pub fn write_atomically(path: &Path, content: &str) -> Result<()> {
+    let file_name = path
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or_default();
+    let temp_path = path.with_file_name(format!(".{file_name}.tmp"));
+    if let Some(p) = path.parent() {
+        fs::create_dir_all(p)?;
+    }
+    let mut file = fs::File::create(&temp_path)?;
+    file.write_all(content.as_bytes())?;
+    file.sync_all()?;
+    fs::rename(&temp_path, path)?;
+    Ok(())
+}
+
 fn get_config_home() -> Result<PathBuf> {
     env::var(CONFIG_HOME_VAR).map(PathBuf::from).or_else(|_| {
         let mut path = get_xdg_config_home()?;
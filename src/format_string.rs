@@ -1,14 +1,20 @@
 use std::{
     collections::HashMap,
     error::Error,
-    fmt::{Debug, Display},
+    fmt::{Debug, Display, Write},
+    rc::Rc,
+    str::FromStr,
     sync::Arc,
 };
 
+use chrono::{
+    NaiveDate, NaiveTime,
+    format::{Item, StrftimeItems},
+};
 use nom::{
     IResult, Parser,
     branch::alt,
-    bytes::complete::{is_not, tag, take_while1},
+    bytes::complete::{is_not, tag, take_while, take_while1},
     combinator::{opt, recognize},
     multi::{many0, many1},
     sequence::{delimited, pair, preceded},
@@ -26,31 +32,78 @@ impl FormatString {
         variables: &'a HashMap<&str, S>,
     ) -> Result<String, EvalError>
     where
-        S: AsRef<str>,
+        S: Clone + Into<VariableValue>,
     {
         let mut buffer = String::new();
         for part in &self.parts {
             match part {
                 FormatStringPart::Literal(string) => buffer.push_str(string),
-                FormatStringPart::Variable(variable) => match variables.get(variable.as_str()) {
-                    Some(value) => buffer.push_str(value.as_ref()),
-                    None => {
+                FormatStringPart::Variable { name, filters } => {
+                    let Some(value) = variables.get(name.as_str()) else {
                         return Err(EvalError::VarNotFound {
                             provided: variables.keys().copied().map(<Arc<str>>::from).collect(),
-                            requested: Arc::from(variable.as_str()),
+                            requested: Arc::from(name.as_str()),
                         });
+                    };
+                    let mut value: VariableValue = value.clone().into();
+                    for filter in filters {
+                        value = filter
+                            .apply(value)
+                            .map_err(|reason| EvalError::FilterFailed {
+                                variable: Arc::from(name.as_str()),
+                                filter: Arc::from(filter.to_string()),
+                                reason,
+                            })?;
                     }
-                },
+                    write!(buffer, "{value}").expect("writing to a string can't fail");
+                }
             }
         }
         Ok(buffer)
     }
+
+    pub fn uses_variable(&self, name: &str) -> bool {
+        self.parts
+            .iter()
+            .any(|p| matches!(p, FormatStringPart::Variable { name: n, .. } if n == name))
+    }
+}
+impl FromStr for FormatString {
+    type Err = ParseError;
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let parts = match parse_format_string(input) {
+            Ok(("", parts)) => parts,
+            Ok((rem, _)) => return Err(ParseError(format!("failed at '{rem}'"))),
+            Err(e) => return Err(ParseError(e.to_string())),
+        };
+        let parts = parts
+            .into_iter()
+            .map(|part| match part {
+                RawPart::Literal(string) => Ok(FormatStringPart::Literal(string)),
+                RawPart::Variable(name, filters) => Ok(FormatStringPart::Variable {
+                    name: name.to_owned(),
+                    filters: filters
+                        .into_iter()
+                        .map(|(filter, arg)| Filter::new(filter, arg))
+                        .collect::<Result<_, _>>()
+                        .map_err(|e| ParseError(format!("${{{name}}}: {e}")))?,
+                }),
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(FormatString { parts })
+    }
 }
 impl Display for FormatString {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         for part in &self.parts {
             match part {
-                FormatStringPart::Variable(v) => write!(f, "${{{}}}", v)?,
+                FormatStringPart::Variable { name, filters } => {
+                    write!(f, "${{{name}")?;
+                    for filter in filters {
+                        write!(f, "|{filter}")?;
+                    }
+                    write!(f, "}}")?;
+                }
                 FormatStringPart::Literal(s) => write!(f, "{}", s.replace("$", "$$"))?,
             }
         }
@@ -72,11 +125,7 @@ impl<'de> Deserialize<'de> for FormatString {
             where
                 E: serde::de::Error,
             {
-                match parse_format_string(v) {
-                    Ok(("", parsed)) => Ok(parsed),
-                    Ok((rem, _)) => Err(E::custom(format!("failed at '{rem}'"))),
-                    Err(e) => Err(E::custom(e)),
-                }
+                FormatString::from_str(v).map_err(E::custom)
             }
         }
 
@@ -92,10 +141,164 @@ impl Serialize for FormatString {
     }
 }
 
+/// The value of a variable, numbers keep their precision until they are written
+#[derive(Debug, Clone)]
+pub enum VariableValue {
+    Text(Rc<str>),
+    /// A number written with this many decimal places
+    Number {
+        value: f64,
+        places: usize,
+    },
+}
+impl VariableValue {
+    /// A number written with two decimal places unless it is rounded
+    pub fn number(value: f64) -> Self {
+        VariableValue::Number { value, places: 2 }
+    }
+}
+impl Display for VariableValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            VariableValue::Text(text) => write!(f, "{text}"),
+            VariableValue::Number { value, places } => write!(f, "{value:.places$}"),
+        }
+    }
+}
+impl From<Rc<str>> for VariableValue {
+    fn from(text: Rc<str>) -> Self {
+        VariableValue::Text(text)
+    }
+}
+impl From<&str> for VariableValue {
+    fn from(text: &str) -> Self {
+        VariableValue::Text(Rc::from(text))
+    }
+}
+impl From<String> for VariableValue {
+    fn from(text: String) -> Self {
+        VariableValue::Text(Rc::from(text))
+    }
+}
+
 #[derive(Debug, Clone)]
 pub enum FormatStringPart {
     Literal(String),
-    Variable(String),
+    Variable { name: String, filters: Vec<Filter> },
+}
+
+/// A transformation of a variable's value, like `round:1` in `${hours|round:1}`
+#[derive(Debug, Clone)]
+pub enum Filter {
+    /// Round a number to this many decimal places
+    Round(usize),
+    /// Replace the decimal point of a number
+    Decimal(String),
+    Upper,
+    Lower,
+    /// Keep at most this many characters
+    Truncate(usize),
+    /// Replace an empty value
+    Default(String),
+    /// Reformat a date like 2026-09-14 or a time like 08:30 with strftime specifiers
+    Fmt(String),
+}
+impl Filter {
+    const NAMES: [&str; 7] = [
+        "round", "decimal", "upper", "lower", "truncate", "default", "fmt",
+    ];
+
+    fn new(name: &str, arg: Option<&str>) -> Result<Self, String> {
+        let count = |arg: &str| {
+            arg.parse::<usize>()
+                .map_err(|_| format!("'{arg}' is not a valid count for {name}"))
+        };
+        match (name, arg) {
+            ("round", None) => Ok(Filter::Round(0)),
+            ("round", Some(arg)) => count(arg).map(Filter::Round),
+            ("decimal", Some(arg)) => Ok(Filter::Decimal(arg.to_owned())),
+            ("upper", None) => Ok(Filter::Upper),
+            ("lower", None) => Ok(Filter::Lower),
+            ("truncate", Some(arg)) => count(arg).map(Filter::Truncate),
+            ("default", arg) => Ok(Filter::Default(arg.unwrap_or_default().to_owned())),
+            ("fmt", Some(arg)) => match StrftimeItems::new(arg).any(|i| i == Item::Error) {
+                true => Err(format!("'{arg}' is not a valid date format")),
+                false => Ok(Filter::Fmt(arg.to_owned())),
+            },
+            ("upper" | "lower", Some(_)) => Err(format!("{name} doesn't take an argument")),
+            ("decimal" | "truncate" | "fmt", None) => {
+                Err(format!("{name} needs an argument, like {name}:<value>"))
+            }
+            _ => Err(format!(
+                "unknown filter '{name}', expected one of: {}",
+                Filter::NAMES.join(", ")
+            )),
+        }
+    }
+
+    /// Numbers are rounded from their precise value, text is parsed when a number is needed
+    fn apply(&self, value: VariableValue) -> Result<VariableValue, String> {
+        let number = |value: &VariableValue| match value {
+            VariableValue::Number { value, .. } => Ok(*value),
+            VariableValue::Text(text) => text
+                .parse::<f64>()
+                .map_err(|_| format!("'{text}' is not a number")),
+        };
+        if let Filter::Round(places) = self {
+            return Ok(VariableValue::Number {
+                value: number(&value)?,
+                places: *places,
+            });
+        }
+        let text = value.to_string();
+        let text = match self {
+            Filter::Round(_) => unreachable!("numbers are rounded above"),
+            Filter::Decimal(separator) => {
+                number(&value)?;
+                text.replacen('.', separator, 1)
+            }
+            Filter::Upper => text.to_uppercase(),
+            Filter::Lower => text.to_lowercase(),
+            Filter::Truncate(count) => text.chars().take(*count).collect(),
+            Filter::Default(default) if text.is_empty() => default.clone(),
+            Filter::Default(_) => text,
+            Filter::Fmt(format) => {
+                let mut formatted = String::new();
+                let written = if let Ok(date) = NaiveDate::parse_from_str(&text, "%Y-%m-%d") {
+                    write!(formatted, "{}", date.format(format))
+                } else if let Ok(time) = NaiveTime::parse_from_str(&text, "%H:%M") {
+                    write!(formatted, "{}", time.format(format))
+                } else {
+                    return Err(format!("'{text}' is neither a date nor a time"));
+                };
+                written.map_err(|_| format!("'{format}' can't be used for '{text}'"))?;
+                formatted
+            }
+        };
+        Ok(VariableValue::Text(Rc::from(text)))
+    }
+}
+impl Display for Filter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Filter::Round(places) => write!(f, "round:{places}"),
+            Filter::Decimal(separator) => write!(f, "decimal:{separator}"),
+            Filter::Upper => write!(f, "upper"),
+            Filter::Lower => write!(f, "lower"),
+            Filter::Truncate(count) => write!(f, "truncate:{count}"),
+            Filter::Default(default) => write!(f, "default:{default}"),
+            Filter::Fmt(format) => write!(f, "fmt:{format}"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ParseError(String);
+impl Error for ParseError {}
+impl Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "invalid format string: {}", self.0)
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -104,6 +307,11 @@ pub enum EvalError {
         requested: Arc<str>,
         provided: Vec<Arc<str>>,
     },
+    FilterFailed {
+        variable: Arc<str>,
+        filter: Arc<str>,
+        reason: String,
+    },
 }
 impl Error for EvalError {}
 impl Display for EvalError {
@@ -115,25 +323,51 @@ impl Display for EvalError {
             } => {
                 write!(f, "a variable called '{requested}' was not provided")
             }
+            EvalError::FilterFailed {
+                variable,
+                filter,
+                reason,
+            } => write!(f, "failed to apply {filter} to '{variable}': {reason}"),
         }
     }
 }
 
-fn parse_format_string(input: &str) -> IResult<&str, FormatString> {
-    let (input, parts) = many0(parse_format_string_part).parse(input)?;
-    Ok((input, FormatString { parts }))
+/// A part of a format string with its filters not yet checked
+enum RawPart<'a> {
+    Literal(String),
+    Variable(&'a str, Vec<(&'a str, Option<&'a str>)>),
 }
 
-fn parse_format_string_part(input: &str) -> IResult<&str, FormatStringPart> {
+fn parse_format_string(input: &str) -> IResult<&str, Vec<RawPart<'_>>> {
+    many0(parse_format_string_part).parse(input)
+}
+
+fn parse_format_string_part(input: &str) -> IResult<&str, RawPart<'_>> {
     alt((parse_part_variable, parse_part_literal)).parse(input)
 }
 
-fn parse_part_variable(input: &str) -> IResult<&str, FormatStringPart> {
+fn parse_part_variable(input: &str) -> IResult<&str, RawPart<'_>> {
     alt((
-        delimited(tag("${"), parse_varname, tag("}")),
-        preceded(tag("$"), parse_varname),
+        delimited(
+            tag("${"),
+            pair(parse_varname, many0(parse_filter)),
+            tag("}"),
+        ),
+        preceded(tag("$"), parse_varname).map(|name| (name, Vec::new())),
     ))
-    .map(|s: &str| FormatStringPart::Variable(s.to_owned()))
+    .map(|(name, filters)| RawPart::Variable(name, filters))
+    .parse(input)
+}
+
+/// Parse a filter like `|upper` or `|round:1`, the argument can contain anything but `|` and `}`
+fn parse_filter(input: &str) -> IResult<&str, (&str, Option<&str>)> {
+    preceded(
+        tag("|"),
+        pair(
+            take_while1(|c: char| c.is_ascii_alphabetic()),
+            opt(preceded(tag(":"), take_while(|c| c != '|' && c != '}'))),
+        ),
+    )
     .parse(input)
 }
 
@@ -145,8 +379,106 @@ fn parse_varname(input: &str) -> IResult<&str, &str> {
     .parse(input)
 }
 
-fn parse_part_literal(input: &str) -> IResult<&str, FormatStringPart> {
+fn parse_part_literal(input: &str) -> IResult<&str, RawPart<'_>> {
     many1(alt((is_not("$"), preceded(tag("$"), tag("$")))))
-        .map(|s: Vec<&str>| FormatStringPart::Literal(s.join("")))
+        .map(|s: Vec<&str>| RawPart::Literal(s.join("")))
         .parse(input)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn eval(format: &str, variables: &[(&str, VariableValue)]) -> Result<String, String> {
+        let format = FormatString::from_str(format).map_err(|e| e.to_string())?;
+        let variables = HashMap::from_iter(variables.iter().cloned());
+        format.evaluate(&variables).map_err(|e| e.to_string())
+    }
+
+    #[test]
+    fn parses_filters_with_and_without_arguments() {
+        let format = FormatString::from_str("${hours|round:1|decimal:,} $wbs").unwrap();
+        let FormatStringPart::Variable { name, filters } = &format.parts[0] else {
+            panic!("expected a variable, got {:?}", format.parts[0]);
+        };
+        assert_eq!(name, "hours");
+        assert!(matches!(filters[..], [Filter::Round(1), Filter::Decimal(ref s)] if s == ","));
+        assert!(format.uses_variable("wbs"));
+        assert!(!format.uses_variable("date"));
+    }
+
+    #[test]
+    fn checks_filter_arguments() {
+        for (format, error) in [
+            ("${wbs|upper:x}", "upper doesn't take an argument"),
+            ("${wbs|truncate}", "truncate needs an argument"),
+            ("${hours|round:-1}", "'-1' is not a valid count for round"),
+            ("${date|fmt:%Q}", "'%Q' is not a valid date format"),
+            ("${wbs|shout}", "unknown filter 'shout'"),
+        ] {
+            let Err(e) = FormatString::from_str(format) else {
+                panic!("'{format}' should not parse");
+            };
+            assert!(e.to_string().contains(error), "'{format}': {e}");
+        }
+        assert!(FormatString::from_str("${hours|round}").is_ok());
+        assert!(FormatString::from_str("${wbs|default}").is_ok());
+    }
+
+    #[test]
+    fn display_round_trips() {
+        for format in [
+            "${hours|round:1|decimal:,}h for ${wbs}",
+            "${date|fmt:%d.%m.%Y}",
+            "${description|default:none|truncate:10|upper}",
+            "Paid $$5",
+        ] {
+            let parsed = FormatString::from_str(format).unwrap();
+            assert_eq!(parsed.to_string(), format);
+            let reparsed = FormatString::from_str(&parsed.to_string()).unwrap();
+            assert_eq!(reparsed.to_string(), format);
+        }
+    }
+
+    #[test]
+    fn rounds_numbers_from_their_raw_value() {
+        let hours = [("hours", VariableValue::number(0.745))];
+        assert_eq!(eval("$hours", &hours).unwrap(), "0.74");
+        assert_eq!(eval("${hours|round:1}", &hours).unwrap(), "0.7");
+        assert_eq!(eval("${hours|round:3|decimal:,}", &hours).unwrap(), "0,745");
+        assert_eq!(eval("${hours|round}", &hours).unwrap(), "1");
+        let text = [("hours", VariableValue::from("1.25"))];
+        assert_eq!(eval("${hours|decimal:,}", &text).unwrap(), "1,25");
+        assert_eq!(eval("${hours|round:1}", &text).unwrap(), "1.2");
+        let wbs = [("hours", VariableValue::from("ABC"))];
+        let e = eval("${hours|round:1}", &wbs).unwrap_err();
+        assert!(e.contains("'ABC' is not a number"), "{e}");
+    }
+
+    #[test]
+    fn transforms_text() {
+        let vars = [
+            ("wbs", VariableValue::from("Project-42")),
+            ("description", VariableValue::from("")),
+        ];
+        assert_eq!(eval("${wbs|upper}", &vars).unwrap(), "PROJECT-42");
+        assert_eq!(eval("${wbs|lower|truncate:4}", &vars).unwrap(), "proj");
+        assert_eq!(eval("${description|default:-}", &vars).unwrap(), "-");
+        assert_eq!(eval("${wbs|default:-}", &vars).unwrap(), "Project-42");
+    }
+
+    #[test]
+    fn formats_dates_and_times() {
+        let vars = [
+            ("date", VariableValue::from("2026-09-14")),
+            ("start", VariableValue::from("08:30")),
+            ("wbs", VariableValue::from("ABC")),
+        ];
+        assert_eq!(eval("${date|fmt:%d.%m.%Y}", &vars).unwrap(), "14.09.2026");
+        assert_eq!(eval("${start|fmt:%I:%M %p}", &vars).unwrap(), "08:30 AM");
+        let e = eval("${wbs|fmt:%Y}", &vars).unwrap_err();
+        assert!(e.contains("neither a date nor a time"), "{e}");
+        let e = eval("${date|fmt:%H}", &vars).unwrap_err();
+        assert!(e.contains("can't be used for '2026-09-14'"), "{e}");
+    }
+}
@@ -0,0 +1,122 @@
+use std::io::{Write, stdout};
+
+use color_eyre::eyre::{Result, format_err};
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+use crate::cli::OutputFormat;
+
+/// Version of the record layout of all machine readable output
+/// Bump this whenever a field is added, renamed, removed, reordered or changes its meaning
+pub const SCHEMA_VERSION: u32 = 2;
+
+/// A record of machine readable output
+pub trait Record: Serialize {
+    /// Names of the serialized fields in order, the union of all variants for enums
+    /// Delimited formats use them as the header, even if there are no records
+    const FIELDS: &'static [&'static str];
+}
+impl<T: Record> Record for &T {
+    const FIELDS: &'static [&'static str] = T::FIELDS;
+}
+
+#[derive(Serialize)]
+struct VersionedRecord<T> {
+    schema_version: u32,
+    #[serde(flatten)]
+    record: T,
+}
+
+/// Print `records` to stdout in the given format
+/// Every record carries a `schema_version` field
+/// Lists of strings are joined with commas in delimited formats
+pub fn print_records<I, T>(records: I, format: &OutputFormat) -> Result<()>
+where
+    I: IntoIterator<Item = T>,
+    T: Record,
+{
+    write_records(&mut stdout().lock(), records, format)
+}
+
+pub(crate) fn write_records<I, T>(
+    out: &mut impl Write,
+    records: I,
+    format: &OutputFormat,
+) -> Result<()>
+where
+    I: IntoIterator<Item = T>,
+    T: Record,
+{
+    let records = records
+        .into_iter()
+        .map(|record| {
+            serde_json::to_value(VersionedRecord {
+                schema_version: SCHEMA_VERSION,
+                record,
+            })
+        })
+        .collect::<serde_json::Result<Vec<_>>>()?;
+
+    match format {
+        OutputFormat::Json => writeln!(out, "{}", serde_json::to_string_pretty(&records)?)?,
+        OutputFormat::Jsonl => {
+            for record in &records {
+                writeln!(out, "{}", serde_json::to_string(record)?)?;
+            }
+        }
+        OutputFormat::Csv => write_delimited(out, &records, T::FIELDS, ',')?,
+        OutputFormat::Tsv => write_delimited(out, &records, T::FIELDS, '\t')?,
+    }
+    Ok(())
+}
+
+fn write_delimited(
+    out: &mut impl Write,
+    records: &[Value],
+    fields: &[&str],
+    delimiter: char,
+) -> Result<()> {
+    let objects = records
+        .iter()
+        .map(|r| {
+            r.as_object()
+                .ok_or_else(|| format_err!("records must be structs to be written as a table"))
+        })
+        .collect::<Result<Vec<&Map<String, Value>>>>()?;
+
+    let mut keys: Vec<&str> = vec!["schema_version"];
+    keys.extend(fields);
+    for key in objects.iter().flat_map(|o| o.keys()) {
+        if !keys.contains(&key.as_str()) {
+            keys.push(key);
+        }
+    }
+    let header: Vec<String> = keys.iter().map(|k| escape_field(k, delimiter)).collect();
+    writeln!(out, "{}", header.join(&delimiter.to_string()))?;
+
+    for object in objects {
+        let fields: Vec<String> = keys
+            .iter()
+            .map(|k| match object.get(*k) {
+                None | Some(Value::Null) => String::new(),
+                Some(Value::String(s)) => escape_field(s, delimiter),
+                Some(Value::Array(items)) if items.iter().all(Value::is_string) => {
+                    let items: Vec<&str> = items.iter().filter_map(Value::as_str).collect();
+                    escape_field(&items.join(","), delimiter)
+                }
+                Some(v) => escape_field(&v.to_string(), delimiter),
+            })
+            .collect();
+        writeln!(out, "{}", fields.join(&delimiter.to_string()))?;
+    }
+    Ok(())
+}
+
+/// Quote fields containing special characters, as described by RFC 4180
+fn escape_field(field: &str, delimiter: char) -> String {
+    if field.contains([delimiter, '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
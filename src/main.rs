@@ -9,17 +9,26 @@ use color_eyre::{
     eyre::{Context, Result},
 };
 
-use crate::{cli::Cli, config::Config};
+use serde::Serialize;
+
+use crate::{cli::Cli, config::Config, machine_readable::Record};
 
 mod activity_commands;
 mod activity_entry;
 mod activity_range;
+mod calendar;
+mod calendar_commands;
 mod cli;
+mod compliance;
 mod config;
 mod entry_commands;
 mod files;
 mod format_string;
+mod machine_readable;
+mod picker;
+mod point_in_time;
 mod printable;
+mod storage;
 mod trackable;
 
 const NONE_PRINT_VALUE: &str = "--";
@@ -38,6 +47,17 @@ fn handle_ttr_command(opts: &Cli) -> Result<()> {
         cli::TtrCommand::End(opts) => {
             entry_commands::end_activity(opts).wrap_err("failed to end tracking")
         }
+        cli::TtrCommand::Pause(opts) => {
+            entry_commands::pause_activity(opts).wrap_err("failed to pause tracking")
+        }
+        cli::TtrCommand::Resume(opts) => {
+            entry_commands::resume_activity(opts).wrap_err("failed to resume tracking")
+        }
+        cli::TtrCommand::Amend(opts) => {
+            entry_commands::amend_entry(opts).wrap_err("failed to amend entry")
+        }
+        cli::TtrCommand::Undo(opts) => entry_commands::undo(opts).wrap_err("failed to undo"),
+        cli::TtrCommand::Redo(opts) => entry_commands::redo(opts).wrap_err("failed to redo"),
         cli::TtrCommand::Show(opts) => {
             entry_commands::show_activities(opts).wrap_err("failed to show activitiy")
         }
@@ -47,7 +67,20 @@ fn handle_ttr_command(opts: &Cli) -> Result<()> {
         cli::TtrCommand::Generate(opts) => {
             entry_commands::handle_generate(opts).wrap_err("failed to generate output")
         }
+        cli::TtrCommand::Check(opts) => {
+            entry_commands::check_entries(opts).wrap_err("failed to check entries")
+        }
+        cli::TtrCommand::Balance(opts) => {
+            entry_commands::show_balance(opts).wrap_err("failed to compute balance")
+        }
+        cli::TtrCommand::CheckCompliance(opts) => {
+            entry_commands::check_compliance(opts).wrap_err("failed to check compliance")
+        }
         cli::TtrCommand::Activity(opts) => handle_activity_command(opts),
+        cli::TtrCommand::Calendar(opts) => handle_calendar_command(opts),
+        cli::TtrCommand::MigrateStorage(opts) => {
+            storage::migrate_storage(opts).wrap_err("failed to migrate storage")
+        }
         cli::TtrCommand::ListAttendanceTypes(opts) => list_attendance_types(opts),
         cli::TtrCommand::MakeConfig => make_guided_config(),
     }
@@ -57,8 +90,10 @@ fn handle_activity_command(activity_command: &cli::ActivityCommand) -> Result<()
     match activity_command {
         cli::ActivityCommand::Set(opts) => activity_commands::set_activity(opts)
             .wrap_err_with(|| format!("failed to set activity '{}'", opts.name)),
-        cli::ActivityCommand::Rm(_) => todo!(),
-        cli::ActivityCommand::Mv(_) => todo!(),
+        cli::ActivityCommand::Rm(opts) => activity_commands::remove_activity(opts)
+            .wrap_err_with(|| format!("failed to remove activity '{}'", opts.name)),
+        cli::ActivityCommand::Mv(opts) => activity_commands::move_activity(opts)
+            .wrap_err_with(|| format!("failed to move activity '{}'", opts.from)),
         cli::ActivityCommand::Ls(opts) => {
             activity_commands::list_activities(opts).wrap_err_with(|| match &opts.name {
                 Some(n) => format!("failed to list activities in {n}"),
@@ -68,11 +103,31 @@ fn handle_activity_command(activity_command: &cli::ActivityCommand) -> Result<()
     }
 }
 
+fn handle_calendar_command(calendar_command: &cli::CalendarCommand) -> Result<()> {
+    match calendar_command {
+        cli::CalendarCommand::Mark(opts) => {
+            calendar_commands::mark_days(opts).wrap_err("failed to mark days")
+        }
+        cli::CalendarCommand::Unmark(opts) => {
+            calendar_commands::unmark_days(opts).wrap_err("failed to unmark days")
+        }
+        cli::CalendarCommand::Ls(opts) => {
+            calendar_commands::list_days(opts).wrap_err("failed to list days")
+        }
+    }
+}
+
 fn list_attendance_types(list_opts: &cli::ListAttendanceTypes) -> Result<()> {
     let config = get_config()?;
     let mut list = config.attendance_types.into_iter().collect::<Vec<_>>();
     list.sort_by(|(_, va), (_, vb)| va.cmp(vb));
-    if list_opts.machine_readable {
+    if let Some(format) = &list_opts.format {
+        let records = list.iter().map(|(number, hint)| AttendanceTypeRecord {
+            number,
+            description: hint,
+        });
+        machine_readable::print_records(records, format)?;
+    } else if list_opts.machine_readable {
         for (number, hint) in list {
             println!("{number}\t{hint}")
         }
@@ -82,18 +137,35 @@ fn list_attendance_types(list_opts: &cli::ListAttendanceTypes) -> Result<()> {
     Ok(())
 }
 
+#[derive(Serialize)]
+struct AttendanceTypeRecord<'a> {
+    number: &'a str,
+    description: &'a str,
+}
+impl Record for AttendanceTypeRecord<'_> {
+    const FIELDS: &'static [&'static str] = &["number", "description"];
+}
+
 fn get_config() -> Result<Config> {
     let config_path = files::get_main_config_path()?;
-    if fs::exists(&config_path)? {
-        let config_str = fs::read_to_string(config_path)?;
-        Ok(toml::from_str(&config_str)?)
-    } else {
-        Err(color_eyre::eyre::format_err!(
+    match get_existing_config()? {
+        Some(config) => Ok(config),
+        None => Err(color_eyre::eyre::format_err!(
             "{config_path:?} does not exist"
         ))
         .wrap_err("Failed to load configuration")
-        .with_note(|| "`make-config` can help you create a configuration file")
+        .with_note(|| "`make-config` can help you create a configuration file"),
+    }
+}
+
+/// Load the configuration, if a configuration file exists
+fn get_existing_config() -> Result<Option<Config>> {
+    let config_path = files::get_main_config_path()?;
+    if !fs::exists(&config_path)? {
+        return Ok(None);
     }
+    let config_str = fs::read_to_string(config_path)?;
+    Ok(Some(toml::from_str(&config_str)?))
 }
 
 fn make_guided_config() -> Result<()> {
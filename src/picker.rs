@@ -0,0 +1,180 @@
+use std::io::{self, IsTerminal, Write};
+
+use color_eyre::eyre::Result;
+#[cfg(unix)]
+use owo_colors::{OwoColorize, Stream};
+
+#[cfg(unix)]
+mod raw_terminal;
+
+/// At most this many matching items are shown at once
+const MAX_SHOWN_ITEMS: usize = 10;
+
+/// Whether the user can be asked to pick something interactively
+pub fn is_available() -> bool {
+    io::stdin().is_terminal() && io::stderr().is_terminal()
+}
+
+/// Let the user pick one of `items`, narrowing them down by typing
+/// Returns the index of the picked item or None if the user cancelled
+#[cfg(unix)]
+pub fn pick(prompt: &str, items: &[String]) -> Result<Option<usize>> {
+    use raw_terminal::Key;
+
+    let _raw_mode = raw_terminal::RawMode::enable()?;
+    let mut out = io::stderr();
+    let mut query = String::new();
+    let mut selected = 0;
+    let picked = loop {
+        let matches = matching_items(&query, items);
+        selected = selected.min(matches.len().saturating_sub(1));
+        render_picker(&mut out, prompt, &query, items, &matches, selected)?;
+        match raw_terminal::read_key()? {
+            Key::Char(c) => {
+                query.push(c);
+                selected = 0;
+            }
+            Key::Backspace => {
+                query.pop();
+                selected = 0;
+            }
+            Key::ClearLine => {
+                query.clear();
+                selected = 0;
+            }
+            Key::Up => selected = selected.saturating_sub(1),
+            Key::Down => selected += 1,
+            Key::Enter => match matches.get(selected) {
+                Some(&i) => break Some(i),
+                None => continue,
+            },
+            Key::Cancel => break None,
+        }
+    };
+    write!(out, "\r\x1b[J")?;
+    out.flush()?;
+    Ok(picked)
+}
+
+/// Let the user pick one of `items`, narrowing them down with each entered line
+/// Returns the index of the picked item or None if the user cancelled
+#[cfg(not(unix))]
+pub fn pick(prompt: &str, items: &[String]) -> Result<Option<usize>> {
+    let mut out = io::stderr();
+    let mut query = String::new();
+    loop {
+        let matches = matching_items(&query, items);
+        for (n, &i) in matches.iter().take(MAX_SHOWN_ITEMS).enumerate() {
+            writeln!(out, "{:>2}) {}", n + 1, items[i])?;
+        }
+        write!(out, "{prompt} (number, filter or empty for the first): ")?;
+        out.flush()?;
+        let mut input = String::new();
+        if io::stdin().read_line(&mut input)? == 0 {
+            return Ok(None);
+        }
+        let input = input.trim();
+        match input.parse::<usize>() {
+            Ok(n) if (1..=matches.len().min(MAX_SHOWN_ITEMS)).contains(&n) => {
+                return Ok(Some(matches[n - 1]));
+            }
+            _ if input.is_empty() && !matches.is_empty() => return Ok(Some(matches[0])),
+            _ => query = input.to_string(),
+        }
+    }
+}
+
+/// Let the user edit a single line of text, starting out with `initial`
+/// Returns None if the user cancelled
+#[cfg(unix)]
+pub fn edit_line(prompt: &str, initial: &str) -> Result<Option<String>> {
+    use raw_terminal::Key;
+
+    let _raw_mode = raw_terminal::RawMode::enable()?;
+    let mut out = io::stderr();
+    let mut line = initial.to_string();
+    let edited = loop {
+        write!(out, "\r\x1b[K{prompt}: {line}")?;
+        out.flush()?;
+        match raw_terminal::read_key()? {
+            Key::Char(c) => line.push(c),
+            Key::Backspace => _ = line.pop(),
+            Key::ClearLine => line.clear(),
+            Key::Up | Key::Down => (),
+            Key::Enter => break Some(line),
+            Key::Cancel => break None,
+        }
+    };
+    write!(out, "\r\n")?;
+    Ok(edited)
+}
+
+/// Let the user enter a single line of text, keeping `initial` if nothing was entered
+/// Returns None if the user cancelled
+#[cfg(not(unix))]
+pub fn edit_line(prompt: &str, initial: &str) -> Result<Option<String>> {
+    let mut out = io::stderr();
+    write!(out, "{prompt} [{initial}]: ")?;
+    out.flush()?;
+    let mut input = String::new();
+    if io::stdin().read_line(&mut input)? == 0 {
+        return Ok(None);
+    }
+    Ok(Some(match input.trim() {
+        "" => initial.to_string(),
+        entered => entered.to_string(),
+    }))
+}
+
+/// Indices of the items containing every word of `query`, ignoring case
+fn matching_items(query: &str, items: &[String]) -> Vec<usize> {
+    let words: Vec<String> = query.split_whitespace().map(str::to_lowercase).collect();
+    items
+        .iter()
+        .enumerate()
+        .filter(|(_, item)| {
+            let item = item.to_lowercase();
+            words.iter().all(|w| item.contains(w.as_str()))
+        })
+        .map(|(i, _)| i)
+        .collect()
+}
+
+/// Draw the query line and the matches below it, leaving the cursor after the query
+#[cfg(unix)]
+fn render_picker(
+    out: &mut impl Write,
+    prompt: &str,
+    query: &str,
+    items: &[String],
+    matches: &[usize],
+    selected: usize,
+) -> io::Result<()> {
+    let width = raw_terminal::width().saturating_sub(1);
+    let first = selected.saturating_sub(MAX_SHOWN_ITEMS - 1);
+    write!(out, "\r\x1b[J{prompt}: {query}")?;
+    let mut lines = 0;
+    for (n, &i) in matches.iter().enumerate().skip(first).take(MAX_SHOWN_ITEMS) {
+        let marker = if n == selected { '>' } else { ' ' };
+        let line: String = format!("{marker} {}", items[i])
+            .chars()
+            .take(width)
+            .collect();
+        match n == selected {
+            true => write!(
+                out,
+                "\r\n{}",
+                line.if_supports_color(Stream::Stderr, |l| l.reversed())
+            )?,
+            false => write!(out, "\r\n{line}")?,
+        }
+        lines += 1;
+    }
+    if matches.is_empty() {
+        write!(out, "\r\n  nothing matches")?;
+        lines += 1;
+    }
+    let column = prompt.chars().count() + 2 + query.chars().count();
+    write!(out, "\x1b[{lines}A\r\x1b[{column}C")?;
+    out.flush()
+}
@@ -0,0 +1,134 @@
+use std::io;
+
+/// Puts the terminal on stdin into raw mode until dropped
+pub struct RawMode {
+    original: libc::termios,
+}
+impl RawMode {
+    pub fn enable() -> io::Result<Self> {
+        let mut termios: libc::termios = unsafe { std::mem::zeroed() };
+        if unsafe { libc::tcgetattr(libc::STDIN_FILENO, &mut termios) } != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        let original = termios;
+        termios.c_lflag &= !(libc::ICANON | libc::ECHO | libc::ISIG | libc::IEXTEN);
+        termios.c_iflag &= !(libc::IXON | libc::ICRNL);
+        termios.c_cc[libc::VMIN] = 1;
+        termios.c_cc[libc::VTIME] = 0;
+        if unsafe { libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &termios) } != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(RawMode { original })
+    }
+}
+impl Drop for RawMode {
+    fn drop(&mut self) {
+        unsafe { libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &self.original) };
+    }
+}
+
+pub enum Key {
+    Char(char),
+    Backspace,
+    ClearLine,
+    Up,
+    Down,
+    Enter,
+    Cancel,
+}
+
+/// Wait for the next key press, the terminal must be in raw mode
+pub fn read_key() -> io::Result<Key> {
+    loop {
+        let key = match read_byte()? {
+            b'\r' | b'\n' => Key::Enter,
+            0x7f | 0x08 => Key::Backspace,
+            // Ctrl-C and Ctrl-D
+            0x03 | 0x04 => Key::Cancel,
+            // Ctrl-U
+            0x15 => Key::ClearLine,
+            // Ctrl-P and Ctrl-N
+            0x10 => Key::Up,
+            0x0e => Key::Down,
+            0x1b if !byte_pending()? => Key::Cancel,
+            0x1b => match read_escape_sequence()? {
+                Some(b'A') => Key::Up,
+                Some(b'B') => Key::Down,
+                _ => continue,
+            },
+            b if b < 0x20 => continue,
+            b => match read_char(b)? {
+                Some(c) => Key::Char(c),
+                None => continue,
+            },
+        };
+        return Ok(key);
+    }
+}
+
+/// Width of the terminal on stderr in columns
+pub fn width() -> usize {
+    let mut size: libc::winsize = unsafe { std::mem::zeroed() };
+    match unsafe { libc::ioctl(libc::STDERR_FILENO, libc::TIOCGWINSZ, &mut size) } {
+        0 if size.ws_col > 0 => size.ws_col as usize,
+        _ => 80,
+    }
+}
+
+/// Read the rest of an escape sequence, returning its final byte
+fn read_escape_sequence() -> io::Result<Option<u8>> {
+    if !matches!(read_byte()?, b'[' | b'O') {
+        return Ok(None);
+    }
+    loop {
+        match read_byte()? {
+            b @ 0x40..=0x7e => return Ok(Some(b)),
+            _ => continue,
+        }
+    }
+}
+
+/// Read the remaining bytes of the UTF-8 character starting with `first`
+fn read_char(first: u8) -> io::Result<Option<char>> {
+    let length = match first.leading_ones() {
+        0 => 1,
+        n @ 2..=4 => n as usize,
+        _ => return Ok(None),
+    };
+    let mut bytes = vec![first];
+    for _ in 1..length {
+        bytes.push(read_byte()?);
+    }
+    Ok(std::str::from_utf8(&bytes)
+        .ok()
+        .and_then(|s| s.chars().next()))
+}
+
+fn read_byte() -> io::Result<u8> {
+    let mut byte = 0u8;
+    loop {
+        match unsafe { libc::read(libc::STDIN_FILENO, (&mut byte as *mut u8).cast(), 1) } {
+            1 => return Ok(byte),
+            0 => return Err(io::ErrorKind::UnexpectedEof.into()),
+            _ => {
+                let error = io::Error::last_os_error();
+                if error.kind() != io::ErrorKind::Interrupted {
+                    return Err(error);
+                }
+            }
+        }
+    }
+}
+
+/// Whether another byte arrives shortly, which tells escape sequences from the escape key
+fn byte_pending() -> io::Result<bool> {
+    let mut poll_fd = libc::pollfd {
+        fd: libc::STDIN_FILENO,
+        events: libc::POLLIN,
+        revents: 0,
+    };
+    match unsafe { libc::poll(&mut poll_fd, 1, 50) } {
+        -1 => Err(io::Error::last_os_error()),
+        n => Ok(n > 0),
+    }
+}
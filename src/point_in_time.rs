@@ -0,0 +1,100 @@
+use std::str::FromStr;
+
+use chrono::{DateTime, Local, NaiveDateTime, NaiveTime, TimeDelta};
+use color_eyre::{
+    Report,
+    eyre::{Result, format_err},
+};
+use nom::{
+    IResult, Parser,
+    bytes::complete::{tag, take_while1},
+    combinator::all_consuming,
+    multi::many1,
+    sequence::pair,
+};
+
+const TIME_FORMATS: [&str; 2] = ["%H:%M", "%H:%M:%S"];
+const DATE_TIME_FORMATS: [&str; 4] = [
+    "%Y-%m-%dT%H:%M",
+    "%Y-%m-%dT%H:%M:%S",
+    "%Y-%m-%d %H:%M",
+    "%Y-%m-%d %H:%M:%S",
+];
+
+/// An absolute point in time, given either as a time of the current day
+/// or as a full local date and time
+#[derive(Debug, Clone)]
+pub enum PointInTime {
+    Today(NaiveTime),
+    Exact(NaiveDateTime),
+}
+impl PointInTime {
+    pub fn resolve(&self, now: &DateTime<Local>) -> Result<DateTime<Local>> {
+        let naive = match self {
+            PointInTime::Today(time) => now.date_naive().and_time(*time),
+            PointInTime::Exact(date_time) => *date_time,
+        };
+        naive
+            .and_local_timezone(Local)
+            .earliest()
+            .ok_or_else(|| format_err!("{naive} does not exist in the local timezone"))
+    }
+}
+impl FromStr for PointInTime {
+    type Err = Report;
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let input = input.trim();
+        if let Some(time) = TIME_FORMATS
+            .iter()
+            .find_map(|f| NaiveTime::parse_from_str(input, f).ok())
+        {
+            return Ok(PointInTime::Today(time));
+        }
+        DATE_TIME_FORMATS
+            .iter()
+            .find_map(|f| NaiveDateTime::parse_from_str(input, f).ok())
+            .map(PointInTime::Exact)
+            .ok_or_else(|| format_err!("expected HH:MM or YYYY-MM-DDTHH:MM, got '{input}'"))
+    }
+}
+
+/// A duration back from now, given as a sequence like `1h30m`
+#[derive(Debug, Clone)]
+pub struct TimeAgo(TimeDelta);
+impl TimeAgo {
+    pub fn resolve(&self, now: &DateTime<Local>) -> Result<DateTime<Local>> {
+        now.checked_sub_signed(self.0)
+            .ok_or_else(|| format_err!("{} hours ago is out of range", self.0.num_hours()))
+    }
+}
+impl FromStr for TimeAgo {
+    type Err = Report;
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let (_, parts) = all_consuming(many1(pair(parse_number, parse_unit)))
+            .parse(input.trim())
+            .map_err(|e| format_err!("Invalid duration: {e}"))?;
+        parts
+            .into_iter()
+            .try_fold(TimeDelta::zero(), |acc, (n, unit)| {
+                acc.checked_add(&unit(n)?)
+            })
+            .map(TimeAgo)
+            .ok_or_else(|| format_err!("Invalid duration: '{}' is too long", input.trim()))
+    }
+}
+
+fn parse_number(input: &str) -> IResult<&str, i64> {
+    take_while1(|c: char| c.is_ascii_digit())
+        .map_res(|s: &str| s.parse::<i64>())
+        .parse(input)
+}
+
+/// Units return None if the number of units is too large to be represented
+fn parse_unit(input: &str) -> IResult<&str, fn(i64) -> Option<TimeDelta>> {
+    nom::branch::alt((
+        tag("h").map(|_| TimeDelta::try_hours as fn(i64) -> Option<TimeDelta>),
+        tag("m").map(|_| TimeDelta::try_minutes as fn(i64) -> Option<TimeDelta>),
+        tag("s").map(|_| TimeDelta::try_seconds as fn(i64) -> Option<TimeDelta>),
+    ))
+    .parse(input)
+}
@@ -0,0 +1,148 @@
+use std::fmt::Display;
+
+use chrono::{DateTime, Local};
+use clap::ValueEnum;
+use color_eyre::{
+    Section,
+    eyre::{Result, format_err},
+};
+use owo_colors::{OwoColorize, Stream};
+use serde::{Deserialize, Serialize};
+
+use crate::{activity_entry::ActivityEntry, cli, files, get_existing_config, trackable::Activity};
+
+#[cfg(feature = "sqlite")]
+mod sqlite;
+mod tsv;
+
+/// Where entries and activities are persisted
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum StorageKind {
+    /// Plain tab separated files, which can be edited by hand
+    #[default]
+    Tsv,
+    /// A single SQLite database, requires the `sqlite` feature
+    Sqlite,
+}
+impl Display for StorageKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StorageKind::Tsv => write!(f, "tsv"),
+            StorageKind::Sqlite => write!(f, "sqlite"),
+        }
+    }
+}
+
+/// Persistence of recorded entries and defined activities
+///
+/// Entries are kept in the order they were recorded, which is chronological
+/// unless they were edited by hand. Writers must hold the matching file lock.
+pub trait Storage {
+    /// Record `entry` after all other entries
+    fn append_entry(&self, entry: &ActivityEntry) -> Result<()>;
+
+    /// Iterate over all entries, starting with the last one
+    fn entries_backwards(&self) -> Result<Box<dyn Iterator<Item = Result<ActivityEntry>> + '_>>;
+
+    /// Replace all recorded entries with `entries`
+    fn rewrite_entries(&self, entries: &[ActivityEntry]) -> Result<()>;
+
+    /// Fetch all defined activities, excluding builtin activities
+    fn activities(&self) -> Result<Vec<Activity>>;
+
+    /// Replace all defined activities with `activities`
+    fn rewrite_activities(&self, activities: &[Activity]) -> Result<()>;
+
+    /// Fetch all recorded entries
+    fn all_entries(&self) -> Result<Vec<ActivityEntry>> {
+        let mut entries = self.entries_backwards()?.collect::<Result<Vec<_>>>()?;
+        entries.reverse();
+        Ok(entries)
+    }
+
+    /// Fetch the last recorded entry
+    fn last_entry(&self) -> Result<Option<ActivityEntry>> {
+        self.entries_backwards()?.next().transpose()
+    }
+
+    /// Fetch entries since `start_time` in reversed order
+    /// The first entry before `start_time` will also be included
+    fn entries_backwards_since(&self, start_time: &DateTime<Local>) -> Result<Vec<ActivityEntry>> {
+        let mut entries = Vec::new();
+        for entry in self.entries_backwards()? {
+            let entry = entry?;
+            let time = *entry.time_stamp();
+            entries.push(entry);
+            if &time <= start_time {
+                break;
+            }
+        }
+        Ok(entries)
+    }
+}
+
+/// Open the storage selected in the config file, defaulting to tsv
+pub fn open() -> Result<Box<dyn Storage>> {
+    open_kind(configured_kind()?)
+}
+
+pub fn configured_kind() -> Result<StorageKind> {
+    Ok(get_existing_config()?
+        .map(|c| c.storage)
+        .unwrap_or_default())
+}
+
+fn open_kind(kind: StorageKind) -> Result<Box<dyn Storage>> {
+    match kind {
+        StorageKind::Tsv => Ok(Box::new(tsv::TsvStorage::new()?)),
+        #[cfg(feature = "sqlite")]
+        StorageKind::Sqlite => Ok(Box::new(sqlite::SqliteStorage::open()?)),
+        #[cfg(not(feature = "sqlite"))]
+        StorageKind::Sqlite => Err(format_err!("timetrack was built without sqlite support"))
+            .with_note(|| "rebuild with `cargo install --features sqlite`"),
+    }
+}
+
+pub fn migrate_storage(migrate_opts: &cli::MigrateStorage) -> Result<()> {
+    let from_kind = configured_kind()?;
+    let to_kind = migrate_opts.to;
+    if from_kind == to_kind {
+        return Err(format_err!("you are already using the {to_kind} storage"));
+    }
+    let _activity_lock = files::lock_activity_file()?;
+    let _entry_lock = files::lock_entry_file()?;
+    let from = open_kind(from_kind)?;
+    let to = open_kind(to_kind)?;
+
+    if !migrate_opts.force && (to.last_entry()?.is_some() || !to.activities()?.is_empty()) {
+        return Err(format_err!("the {to_kind} storage already contains data"))
+            .with_note(|| "use --force to overwrite it");
+    }
+
+    let entries = from
+        .all_entries()
+        .with_note(|| "use `timetrack check --fix` to repair the entry log first")?;
+    let activities = from.activities()?;
+    to.rewrite_entries(&entries)?;
+    to.rewrite_activities(&activities)?;
+
+    let entries_match = (to.all_entries()?.iter().map(ToString::to_string))
+        .eq(entries.iter().map(ToString::to_string));
+    let activities_match = (to.activities()?.iter().map(ToString::to_string))
+        .eq(activities.iter().map(ToString::to_string));
+    if !entries_match || !activities_match {
+        return Err(format_err!(
+            "migrated data does not match the {from_kind} storage"
+        ));
+    }
+
+    println!(
+        "Migrated {} entries and {} activities from {from_kind} to {}",
+        entries.len(),
+        activities.len(),
+        to_kind.if_supports_color(Stream::Stdout, |n| n.green())
+    );
+    println!("Set `storage = \"{to_kind}\"` in your config file to start using it");
+    Ok(())
+}
@@ -0,0 +1,290 @@
+use std::str::FromStr;
+
+use chrono::{DateTime, Local};
+use color_eyre::eyre::{Result, format_err};
+use rusqlite::{Connection, Row, params};
+
+use crate::{
+    activity_entry::{ActivityEntry, TAG_SEPARATOR},
+    files,
+    trackable::Activity,
+};
+
+use super::Storage;
+
+const PAGE_SIZE: i64 = 256;
+const ENTRY_COLUMNS: &str = "id, time_stamp, activity, attendance, wbs, description, pause, tags";
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS entries (
+        id INTEGER PRIMARY KEY,
+        time_stamp TEXT NOT NULL,
+        unix_nanos INTEGER NOT NULL,
+        activity TEXT,
+        attendance TEXT,
+        wbs TEXT,
+        description TEXT,
+        pause INTEGER NOT NULL DEFAULT 0,
+        tags TEXT
+    );
+    CREATE INDEX IF NOT EXISTS entries_by_time ON entries (unix_nanos);
+    CREATE TABLE IF NOT EXISTS activities (
+        id INTEGER PRIMARY KEY,
+        path TEXT NOT NULL,
+        wbs TEXT NOT NULL,
+        description TEXT,
+        aliases TEXT,
+        attendance TEXT
+    );
+";
+/// Columns added after a database may have been created
+const ADDED_COLUMNS: &[(&str, &str)] = &[
+    ("activities", "aliases TEXT"),
+    ("activities", "attendance TEXT"),
+    ("entries", "pause INTEGER NOT NULL DEFAULT 0"),
+    ("entries", "tags TEXT"),
+];
+
+/// Entries and activities in a single SQLite database
+/// Entries without an activity are end entries, the row id keeps the recorded order
+pub struct SqliteStorage {
+    connection: Connection,
+}
+impl SqliteStorage {
+    pub fn open() -> Result<Self> {
+        let path = files::get_database_path()?;
+        if let Some(p) = path.parent() {
+            std::fs::create_dir_all(p)?;
+        }
+        let connection = Connection::open(path)?;
+        connection.execute_batch(SCHEMA)?;
+        add_missing_columns(&connection)?;
+        Ok(SqliteStorage { connection })
+    }
+
+    fn query_entries(&self, sql: &str, params: impl rusqlite::Params) -> Result<Vec<EntryRow>> {
+        let mut statement = self.connection.prepare(sql)?;
+        let rows = statement
+            .query_map(params, EntryRow::from_row)?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(rows)
+    }
+}
+impl Storage for SqliteStorage {
+    fn append_entry(&self, entry: &ActivityEntry) -> Result<()> {
+        insert_entry(&self.connection, entry)
+    }
+
+    fn entries_backwards(&self) -> Result<Box<dyn Iterator<Item = Result<ActivityEntry>> + '_>> {
+        Ok(Box::new(BackwardsEntries {
+            storage: self,
+            before_id: i64::MAX,
+            page: Vec::new().into_iter(),
+        }))
+    }
+
+    fn rewrite_entries(&self, entries: &[ActivityEntry]) -> Result<()> {
+        let transaction = self.connection.unchecked_transaction()?;
+        transaction.execute("DELETE FROM entries", [])?;
+        for entry in entries {
+            insert_entry(&transaction, entry)?;
+        }
+        transaction.commit()?;
+        Ok(())
+    }
+
+    fn activities(&self) -> Result<Vec<Activity>> {
+        let mut statement = self.connection.prepare(
+            "SELECT path, wbs, description, aliases, attendance FROM activities ORDER BY id",
+        )?;
+        let rows = statement
+            .query_map([], |row| {
+                let path: String = row.get(0)?;
+                let wbs: String = row.get(1)?;
+                let description: Option<String> = row.get(2)?;
+                let aliases: Option<String> = row.get(3)?;
+                let attendance: Option<String> = row.get(4)?;
+                let options: String = attendance
+                    .iter()
+                    .map(|a| format!("\tattendance={a}"))
+                    .chain(
+                        aliases
+                            .iter()
+                            .flat_map(|a| a.split(','))
+                            .map(|a| format!("\talias={a}")),
+                    )
+                    .collect();
+                Ok(format!(
+                    "{path}\t{wbs}\t{}{options}",
+                    description.unwrap_or_default()
+                ))
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        let activities = rows
+            .iter()
+            .map(|r| Activity::from_str(r))
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        Ok(activities)
+    }
+
+    fn rewrite_activities(&self, activities: &[Activity]) -> Result<()> {
+        let transaction = self.connection.unchecked_transaction()?;
+        transaction.execute("DELETE FROM activities", [])?;
+        for activity in activities {
+            transaction.execute(
+                "INSERT INTO activities (path, wbs, description, aliases, attendance)
+                VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![
+                    activity.full_path(),
+                    activity.wbs(),
+                    activity.description(),
+                    (!activity.aliases().is_empty()).then(|| activity.aliases().join(",")),
+                    activity.attendance()
+                ],
+            )?;
+        }
+        transaction.commit()?;
+        Ok(())
+    }
+
+    fn entries_backwards_since(&self, start_time: &DateTime<Local>) -> Result<Vec<ActivityEntry>> {
+        let sql = format!(
+            "SELECT {ENTRY_COLUMNS} FROM entries WHERE id >= COALESCE(
+                (SELECT MAX(id) FROM entries WHERE unix_nanos <= ?1), 0
+            ) ORDER BY id DESC"
+        );
+        self.query_entries(&sql, [unix_nanos(start_time)?])?
+            .into_iter()
+            .map(EntryRow::into_entry)
+            .collect()
+    }
+}
+
+/// Pages through the entries table, starting with the highest row id
+struct BackwardsEntries<'a> {
+    storage: &'a SqliteStorage,
+    before_id: i64,
+    page: std::vec::IntoIter<EntryRow>,
+}
+impl Iterator for BackwardsEntries<'_> {
+    type Item = Result<ActivityEntry>;
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(row) = self.page.next() {
+            return Some(row.into_entry());
+        }
+        let sql =
+            format!("SELECT {ENTRY_COLUMNS} FROM entries WHERE id < ?1 ORDER BY id DESC LIMIT ?2");
+        let rows = match self
+            .storage
+            .query_entries(&sql, [self.before_id, PAGE_SIZE])
+        {
+            Ok(rows) => rows,
+            Err(e) => return Some(Err(e)),
+        };
+        self.before_id = rows.last()?.id;
+        self.page = rows.into_iter();
+        self.page.next().map(EntryRow::into_entry)
+    }
+}
+
+struct EntryRow {
+    id: i64,
+    time_stamp: String,
+    activity: Option<String>,
+    attendance: Option<String>,
+    wbs: Option<String>,
+    description: Option<String>,
+    pause: bool,
+    tags: Option<String>,
+}
+impl EntryRow {
+    fn from_row(row: &Row) -> rusqlite::Result<Self> {
+        Ok(EntryRow {
+            id: row.get(0)?,
+            time_stamp: row.get(1)?,
+            activity: row.get(2)?,
+            attendance: row.get(3)?,
+            wbs: row.get(4)?,
+            description: row.get(5)?,
+            pause: row.get(6)?,
+            tags: row.get(7)?,
+        })
+    }
+
+    fn into_entry(self) -> Result<ActivityEntry> {
+        let time_stamp = DateTime::from_str(&self.time_stamp)?;
+        let Some(activity) = self.activity else {
+            return Ok(match self.pause {
+                true => ActivityEntry::new_pause(time_stamp),
+                false => ActivityEntry::new_end(time_stamp),
+            });
+        };
+        Ok(ActivityEntry::new_start(
+            time_stamp,
+            &activity,
+            self.attendance.as_deref().unwrap_or_default(),
+            self.wbs.as_deref().unwrap_or_default(),
+            self.description.as_deref().unwrap_or_default(),
+            &self
+                .tags
+                .as_deref()
+                .map_or(Vec::new(), |t| t.split(TAG_SEPARATOR).collect()),
+        ))
+    }
+}
+
+fn insert_entry(connection: &Connection, entry: &ActivityEntry) -> Result<()> {
+    let time_stamp = entry.time_stamp();
+    let sql = "INSERT INTO entries
+        (time_stamp, unix_nanos, activity, attendance, wbs, description, pause, tags)
+        VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)";
+    match entry {
+        ActivityEntry::Start(start) => connection.execute(
+            sql,
+            params![
+                time_stamp.to_string(),
+                unix_nanos(time_stamp)?,
+                start.name(),
+                start.attendance(),
+                start.wbs(),
+                start.description(),
+                false,
+                (!start.tags().is_empty()).then(|| start.tags().join(&TAG_SEPARATOR.to_string()))
+            ],
+        )?,
+        ActivityEntry::End(_) => connection.execute(
+            sql,
+            params![
+                time_stamp.to_string(),
+                unix_nanos(time_stamp)?,
+                None::<&str>,
+                None::<&str>,
+                None::<&str>,
+                None::<&str>,
+                entry.is_pause(),
+                None::<&str>
+            ],
+        )?,
+    };
+    Ok(())
+}
+
+fn add_missing_columns(connection: &Connection) -> Result<()> {
+    for (table, column) in ADDED_COLUMNS {
+        let name = column.split_whitespace().next().unwrap_or_default();
+        let exists: bool = connection.query_row(
+            "SELECT COUNT(*) > 0 FROM pragma_table_info(?1) WHERE name = ?2",
+            params![table, name],
+            |row| row.get(0),
+        )?;
+        if !exists {
+            connection.execute(&format!("ALTER TABLE {table} ADD COLUMN {column}"), [])?;
+        }
+    }
+    Ok(())
+}
+
+fn unix_nanos(time_stamp: &DateTime<Local>) -> Result<i64> {
+    time_stamp
+        .timestamp_nanos_opt()
+        .ok_or_else(|| format_err!("{time_stamp} can't be stored in sqlite"))
+}
@@ -0,0 +1,104 @@
+use std::{fs, io, path::PathBuf, str::FromStr};
+
+use color_eyre::eyre::{Context, Result};
+use rev_lines::RawRevLines;
+
+use crate::{activity_entry::ActivityEntry, files, trackable::Activity};
+
+use super::Storage;
+
+/// Entries and activities in tab separated files, one record per line
+pub struct TsvStorage {
+    entry_path: PathBuf,
+    activity_path: PathBuf,
+}
+impl TsvStorage {
+    pub fn new() -> Result<Self> {
+        Ok(TsvStorage {
+            entry_path: files::get_entry_file_path()?,
+            activity_path: files::get_activity_file_path()?,
+        })
+    }
+}
+impl Storage for TsvStorage {
+    fn append_entry(&self, entry: &ActivityEntry) -> Result<()> {
+        files::append_line(&self.entry_path, &entry.to_string())
+    }
+
+    fn entries_backwards(&self) -> Result<Box<dyn Iterator<Item = Result<ActivityEntry>> + '_>> {
+        if !fs::exists(&self.entry_path)? {
+            return Ok(Box::new(std::iter::empty()));
+        }
+        let file = fs::File::open(&self.entry_path)?;
+        Ok(Box::new(RawRevLines::new(file).map(entry_from_byte_result)))
+    }
+
+    fn rewrite_entries(&self, entries: &[ActivityEntry]) -> Result<()> {
+        let content: String = entries.iter().map(|e| format!("{e}\n")).collect();
+        files::write_atomically(&self.entry_path, &content)
+    }
+
+    fn activities(&self) -> Result<Vec<Activity>> {
+        if !fs::exists(&self.activity_path)? {
+            return Ok(Vec::new());
+        }
+        parse_activities(&fs::read_to_string(&self.activity_path)?)
+    }
+
+    fn rewrite_activities(&self, activities: &[Activity]) -> Result<()> {
+        let content: String = activities.iter().map(|a| format!("{a}\n")).collect();
+        files::write_atomically(&self.activity_path, &content)
+    }
+
+    fn all_entries(&self) -> Result<Vec<ActivityEntry>> {
+        if !fs::exists(&self.entry_path)? {
+            return Ok(Vec::new());
+        }
+        let entries = fs::read_to_string(&self.entry_path)?
+            .lines()
+            .map(ActivityEntry::from_str)
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        Ok(entries)
+    }
+}
+
+/// Parse the lines of an activity file, errors name the line like `check` does
+fn parse_activities(content: &str) -> Result<Vec<Activity>> {
+    content
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| {
+            Activity::from_str(line).wrap_err_with(|| {
+                format!(
+                    "invalid activity at activities:{}: '{}'",
+                    i + 1,
+                    line.escape_debug()
+                )
+            })
+        })
+        .collect()
+}
+
+fn entry_from_byte_result(
+    byte_result: std::result::Result<Vec<u8>, io::Error>,
+) -> Result<ActivityEntry> {
+    let entry_str = String::from_utf8(byte_result?)?;
+    Ok(ActivityEntry::from_str(&entry_str)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn activity_errors_name_the_line() {
+        let content = "work\tI.00309999.99.99\n\nbroken\n";
+        let e = parse_activities(content).unwrap_err();
+        assert_eq!(e.to_string(), "invalid activity at activities:3: 'broken'");
+        assert_eq!(e.root_cause().to_string(), "missing wbs");
+
+        let activities = parse_activities("work\tI.00309999.99.99\n\n").unwrap();
+        assert_eq!(activities.len(), 1);
+    }
+}
@@ -7,14 +7,25 @@ use std::{
     sync::Arc,
 };
 
+use serde::{Serialize, ser::SerializeStruct};
+
+use crate::{
+    format_string::{self, FormatString},
+    machine_readable::Record,
+};
+
 pub const BUILTIN_ACTIVITY_IDLE_NAME: &str = "idle";
 pub const BUILTIN_ACTIVITY_IDLE_WBS: &str = "Idle";
+/// Keys of optional `key=value` fields after the description, older versions ignore these fields
+const ALIAS_OPTION: &str = "alias";
+const ATTENDANCE_OPTION: &str = "attendance";
 
 #[derive(Debug, Clone)]
 pub enum ParseActivityErr {
     MissingPath,
     NoNameInPath,
     MissingWbs,
+    InvalidOption(String),
 }
 impl error::Error for ParseActivityErr {}
 impl Display for ParseActivityErr {
@@ -23,6 +34,9 @@ impl Display for ParseActivityErr {
             ParseActivityErr::MissingPath => write!(f, "missing path"),
             ParseActivityErr::MissingWbs => write!(f, "missing wbs"),
             ParseActivityErr::NoNameInPath => write!(f, "path doesn't end in a name"),
+            ParseActivityErr::InvalidOption(field) => {
+                write!(f, "expected an option like key=value, got '{field}'")
+            }
         }
     }
 }
@@ -40,6 +54,8 @@ impl Activity {
                 name: Rc::from(BUILTIN_ACTIVITY_IDLE_NAME),
                 wbs: Rc::from(BUILTIN_ACTIVITY_IDLE_WBS),
                 default_description: None,
+                default_attendance: None,
+                aliases: Vec::new(),
             },
         }
     }
@@ -57,6 +73,16 @@ impl Activity {
     pub fn description(&self) -> Option<&str> {
         self.leaf.default_description.as_deref()
     }
+    /// The default description, which can contain variables like `$date`
+    pub fn description_template(&self) -> Result<Option<FormatString>, format_string::ParseError> {
+        self.description().map(FormatString::from_str).transpose()
+    }
+    pub fn attendance(&self) -> Option<&str> {
+        self.leaf.attendance()
+    }
+    pub fn aliases(&self) -> &[Rc<str>] {
+        self.leaf.aliases()
+    }
 }
 impl From<ActivityLeaf> for Activity {
     fn from(leaf: ActivityLeaf) -> Self {
@@ -69,10 +95,7 @@ impl From<ActivityLeaf> for Activity {
 impl Display for Activity {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let path: String = self.path.iter().map(|s| format!("{s}/")).collect();
-        let descr = self.leaf.default_description.as_deref().unwrap_or_default();
-        let name = &self.leaf.name;
-        let wbs = &self.leaf.wbs;
-        write!(f, "{path}{name}\t{wbs}\t{descr}")
+        write!(f, "{path}{}", self.leaf)
     }
 }
 impl FromStr for Activity {
@@ -88,6 +111,18 @@ impl FromStr for Activity {
             None | Some("") => None,
             Some(d) => Some(Rc::from(d)),
         };
+        let mut aliases = Vec::new();
+        let mut default_attendance = None;
+        for field in fields {
+            match field.split_once('=') {
+                Some((ALIAS_OPTION, alias)) => aliases.push(Rc::from(alias)),
+                Some((ATTENDANCE_OPTION, attendance)) => {
+                    default_attendance = Some(Rc::from(attendance))
+                }
+                Some(_) => (),
+                None => return Err(ParseActivityErr::InvalidOption(field.to_string())),
+            }
+        }
         let mut path: VecDeque<Rc<str>> = path.split("/").map(Rc::from).collect();
         let name = path.pop_back().ok_or(ParseActivityErr::NoNameInPath)?;
         if name.is_empty() {
@@ -97,6 +132,8 @@ impl FromStr for Activity {
             name,
             wbs,
             default_description,
+            default_attendance,
+            aliases,
         };
 
         Ok(Activity { path, leaf })
@@ -108,6 +145,12 @@ pub enum ActivityItemRef<'a> {
     Leaf(&'a ActivityLeaf),
     Category(&'a ActivityCategory),
 }
+#[derive(Debug, Clone)]
+pub enum ActivityItem {
+    Leaf(ActivityLeaf),
+    Category(ActivityCategory),
+}
+
 #[derive(Debug, Clone)]
 pub enum LookupError {
     NotACategory(Vec<Arc<str>>),
@@ -134,6 +177,27 @@ impl Display for LookupError {
 }
 
 #[derive(Debug, Clone)]
+pub enum InsertError {
+    NotACategory(Vec<Arc<str>>),
+    IsACategory(Vec<Arc<str>>),
+    AlreadyExists(Vec<Arc<str>>),
+}
+impl std::error::Error for InsertError {}
+impl Display for InsertError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            InsertError::NotACategory(path) => {
+                write!(f, "{} is an activity, not a category", path.join("/"))
+            }
+            InsertError::IsACategory(path) => {
+                write!(f, "{} is a category, not an activity", path.join("/"))
+            }
+            InsertError::AlreadyExists(path) => write!(f, "{} already exists", path.join("/")),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
 pub struct ActivityCategory {
     pub branches: HashMap<Rc<str>, Self>,
     pub leafs: HashMap<Rc<str>, ActivityLeaf>,
@@ -180,12 +244,108 @@ impl ActivityCategory {
                 .map(ActivityItemRef::Category)
                 .or(category.leafs.get(&part).map(ActivityItemRef::Leaf))
                 .ok_or_else(|| LookupError::NoSuchItem {
-                    path: path.iter().copied().map(Arc::from).collect(),
+                    path: path[..i].iter().copied().map(Arc::from).collect(),
                     requested: Arc::from(path[i]),
                 })?;
         }
         Ok(out)
     }
+
+    /// Remove the item called `name` from the category at `path`
+    pub fn remove_item(&mut self, path: &[&str], name: &str) -> Result<ActivityItem, LookupError> {
+        let category = self.get_category_mut(path)?;
+        category
+            .branches
+            .remove(name)
+            .map(ActivityItem::Category)
+            .or_else(|| category.leafs.remove(name).map(ActivityItem::Leaf))
+            .ok_or_else(|| LookupError::NoSuchItem {
+                path: path.iter().copied().map(Arc::from).collect(),
+                requested: Arc::from(name),
+            })
+    }
+
+    fn get_category_mut(&mut self, path: &[&str]) -> Result<&mut ActivityCategory, LookupError> {
+        let mut category = self;
+        for (i, part) in path.iter().copied().enumerate() {
+            if category.leafs.contains_key(part) {
+                let path = path[..=i].iter().copied().map(Arc::from).collect();
+                return Err(LookupError::NotACategory(path));
+            }
+            category = category
+                .branches
+                .get_mut(part)
+                .ok_or_else(|| LookupError::NoSuchItem {
+                    path: path[..i].iter().copied().map(Arc::from).collect(),
+                    requested: Arc::from(part),
+                })?;
+        }
+        Ok(category)
+    }
+
+    /// Insert `leaf` into the category at `path`, creating missing categories on the way
+    /// Returns the replaced leaf, if `overwrite` is set and a leaf of the same name existed
+    pub fn insert_leaf(
+        &mut self,
+        path: &[&str],
+        leaf: ActivityLeaf,
+        overwrite: bool,
+    ) -> Result<Option<ActivityLeaf>, InsertError> {
+        let full_path = || {
+            path.iter()
+                .copied()
+                .chain(Some(leaf.name()))
+                .map(Arc::from)
+                .collect()
+        };
+        let category = self.get_or_create_category(path)?;
+        if category.branches.contains_key(leaf.name()) {
+            return Err(InsertError::IsACategory(full_path()));
+        }
+        if !overwrite && category.leafs.contains_key(leaf.name()) {
+            return Err(InsertError::AlreadyExists(full_path()));
+        }
+        Ok(category.leafs.insert(leaf.name.clone(), leaf))
+    }
+
+    /// Insert `item` into the category at `path` under a new `name`
+    /// Fails if anything called `name` already exists in that category
+    pub fn insert_item(
+        &mut self,
+        path: &[&str],
+        name: &str,
+        item: ActivityItem,
+    ) -> Result<(), InsertError> {
+        let category = self.get_or_create_category(path)?;
+        if category.branches.contains_key(name) || category.leafs.contains_key(name) {
+            let path = path.iter().copied().chain(Some(name)).map(Arc::from);
+            return Err(InsertError::AlreadyExists(path.collect()));
+        }
+        let name = Rc::from(name);
+        match item {
+            ActivityItem::Category(c) => _ = category.branches.insert(name, c),
+            ActivityItem::Leaf(l) => {
+                let leaf = ActivityLeaf {
+                    name: name.clone(),
+                    ..l
+                };
+                category.leafs.insert(name, leaf);
+            }
+        }
+        Ok(())
+    }
+
+    fn get_or_create_category(&mut self, path: &[&str]) -> Result<&mut Self, InsertError> {
+        let mut category = self;
+        for (i, part) in path.iter().copied().enumerate() {
+            if category.leafs.contains_key(part) {
+                let path = path[..=i].iter().copied().map(Arc::from).collect();
+                return Err(InsertError::NotACategory(path));
+            }
+            category = category.branches.entry(Rc::from(part)).or_default();
+        }
+        Ok(category)
+    }
 }
 impl<I> From<I> for ActivityCategory
 where
@@ -216,9 +376,35 @@ where
 pub struct ActivityLeaf {
     name: Rc<str>,
     wbs: Rc<str>,
+    /// Can contain variables like `$date`, which are replaced when starting the activity
     default_description: Option<Rc<str>>,
+    default_attendance: Option<Rc<str>>,
+    /// Alternative names to start this activity with
+    aliases: Vec<Rc<str>>,
 }
 impl ActivityLeaf {
+    pub fn new(name: &str, wbs: &str, default_description: Option<&str>) -> Self {
+        ActivityLeaf {
+            name: Rc::from(name),
+            wbs: Rc::from(wbs),
+            default_description: default_description.map(Rc::from),
+            default_attendance: None,
+            aliases: Vec::new(),
+        }
+    }
+    pub fn with_attendance(self, attendance: Option<&str>) -> Self {
+        ActivityLeaf {
+            default_attendance: attendance.map(Rc::from),
+            ..self
+        }
+    }
+    pub fn with_aliases(self, aliases: &[String]) -> Self {
+        ActivityLeaf {
+            aliases: aliases.iter().map(|a| Rc::from(a.as_str())).collect(),
+            ..self
+        }
+    }
+
     pub fn name(&self) -> &str {
         &self.name
     }
@@ -228,13 +414,26 @@ impl ActivityLeaf {
     pub fn description(&self) -> Option<&str> {
         self.default_description.as_deref()
     }
+    pub fn attendance(&self) -> Option<&str> {
+        self.default_attendance.as_deref()
+    }
+    pub fn aliases(&self) -> &[Rc<str>] {
+        &self.aliases
+    }
 }
 impl Display for ActivityLeaf {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let descr = self.default_description.as_deref().unwrap_or_default();
         let name = &self.name;
         let wbs = &self.wbs;
-        write!(f, "{name}\t{wbs}\t{descr}")
+        write!(f, "{name}\t{wbs}\t{descr}")?;
+        if let Some(attendance) = &self.default_attendance {
+            write!(f, "\t{ATTENDANCE_OPTION}={attendance}")?;
+        }
+        for alias in &self.aliases {
+            write!(f, "\t{ALIAS_OPTION}={alias}")?;
+        }
+        Ok(())
     }
 }
 
@@ -265,6 +464,20 @@ impl PrintableActivityItem<'_> {
             PrintableActivityItem::Activity(activity) => activity.description(),
         }
     }
+    pub fn attendance(&self) -> Option<&str> {
+        match self {
+            PrintableActivityItem::CategoryName(_) => None,
+            PrintableActivityItem::ActivityLeaf(leaf) => leaf.attendance(),
+            PrintableActivityItem::Activity(activity) => activity.attendance(),
+        }
+    }
+    pub fn aliases(&self) -> &[Rc<str>] {
+        match self {
+            PrintableActivityItem::CategoryName(_) => &[],
+            PrintableActivityItem::ActivityLeaf(leaf) => leaf.aliases(),
+            PrintableActivityItem::Activity(activity) => activity.aliases(),
+        }
+    }
 }
 impl Display for PrintableActivityItem<'_> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -275,3 +488,21 @@ impl Display for PrintableActivityItem<'_> {
         }
     }
 }
+impl Serialize for PrintableActivityItem<'_> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let mut s = serializer.serialize_struct("PrintableActivityItem", 5)?;
+        s.serialize_field("name", &self.display_name())?;
+        s.serialize_field("wbs", &self.wbs())?;
+        s.serialize_field("description", &self.description())?;
+        s.serialize_field("attendance", &self.attendance())?;
+        s.serialize_field("aliases", &self.aliases())?;
+        s.end()
+    }
+}
+impl Record for PrintableActivityItem<'_> {
+    const FIELDS: &'static [&'static str] =
+        &["name", "wbs", "description", "attendance", "aliases"];
+}
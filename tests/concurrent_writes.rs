@@ -0,0 +1,131 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process::{Command, Output},
+    thread,
+};
+
+const WRITERS: usize = 16;
+
+/// A config and data home that is removed again when dropped
+struct Home(PathBuf);
+impl Home {
+    fn new(name: &str) -> Home {
+        let path = std::env::temp_dir().join(format!("timetrack-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&path);
+        fs::create_dir_all(&path).unwrap();
+        fs::write(
+            path.join("config.toml"),
+            include_str!("../assets/default_config.toml"),
+        )
+        .unwrap();
+        Home(path)
+    }
+
+    fn entry_lines(&self) -> Vec<String> {
+        fs::read_to_string(self.0.join("entries"))
+            .unwrap()
+            .lines()
+            .map(String::from)
+            .collect()
+    }
+}
+impl Drop for Home {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
+
+fn timetrack(home: &Path, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_timetrack"))
+        .args(args)
+        .env("TIMETRACK_HOME", home)
+        .env("TIMETRACK_DATA_HOME", home)
+        .env("NO_COLOR", "1")
+        .output()
+        .unwrap()
+}
+
+/// Run `args_for(i)` for every writer at the same time
+fn run_concurrently(home: &Path, args_for: impl Fn(usize) -> Vec<String> + Sync) -> Vec<Output> {
+    thread::scope(|s| {
+        let handles: Vec<_> = (0..WRITERS)
+            .map(|i| {
+                let args = args_for(i);
+                s.spawn(move || {
+                    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+                    timetrack(home, &args)
+                })
+            })
+            .collect();
+        handles.into_iter().map(|h| h.join().unwrap()).collect()
+    })
+}
+
+#[test]
+fn concurrent_starts_are_all_recorded() {
+    let home = Home::new("starts");
+    let outputs = run_concurrently(&home.0, |i| {
+        [
+            "start",
+            "idle",
+            "-d",
+            &format!("writer {i}"),
+            "--ago",
+            &format!("{}m", i + 1),
+        ]
+        .map(String::from)
+        .to_vec()
+    });
+    for output in &outputs {
+        assert!(output.status.success(), "{output:?}");
+    }
+
+    let lines = home.entry_lines();
+    assert_eq!(lines.len(), WRITERS);
+    for i in 0..WRITERS {
+        let description = format!("\twriter {i}");
+        assert!(lines.iter().any(|l| l.ends_with(&description)), "{lines:?}");
+    }
+    assert!(timetrack(&home.0, &["check"]).status.success());
+}
+
+#[test]
+fn rewrites_do_not_lose_concurrent_appends() {
+    let home = Home::new("rewrites");
+    assert!(
+        timetrack(&home.0, &["activity", "set", "a", "I.12345678.01.01"])
+            .status
+            .success()
+    );
+    assert!(
+        timetrack(&home.0, &["start", "a", "--ago", "10h"])
+            .status
+            .success()
+    );
+
+    let outputs = run_concurrently(&home.0, |i| match i % 2 {
+        0 => [
+            "start",
+            "idle",
+            "-d",
+            &format!("writer {i}"),
+            "--ago",
+            &format!("{}m", i + 1),
+        ]
+        .map(String::from)
+        .to_vec(),
+        _ if i % 4 == 1 => ["activity", "mv", "--rewrite-history", "a", "b"]
+            .map(String::from)
+            .to_vec(),
+        _ => ["activity", "mv", "--rewrite-history", "b", "a"]
+            .map(String::from)
+            .to_vec(),
+    });
+    let starts = outputs.iter().step_by(2).filter(|o| o.status.success());
+    assert_eq!(starts.count(), WRITERS / 2);
+
+    let lines = home.entry_lines();
+    assert_eq!(lines.len(), WRITERS / 2 + 1, "{lines:?}");
+    assert!(timetrack(&home.0, &["check"]).status.success());
+}